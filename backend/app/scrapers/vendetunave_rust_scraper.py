@@ -4,7 +4,8 @@ VendeTuNave Rust-backed scraper.
 Delegates the actual HTTP scraping to the compiled Rust binary
 (`vendetunave-scraper`), which queries
 https://www.vendetunave.co/vehiculos/carrosycamionetas using the site's
-search bar and returns a JSON array of vehicle listings.
+search bar and returns a JSON object with the vehicle listings and the
+number of result pages it visited.
 
 The binary is expected to be available either on PATH or at the path
 configured via the VENDETUNAVE_RUST_BIN environment variable.
@@ -68,11 +69,11 @@ class VendeTuNaveRustScraper(BaseScraper):
             try:
                 listing = {
                     "title": item.get("title", ""),
-                    "price": item.get("price"),
+                    "price": self._cop_amount(item.get("price")),
                     "year": item.get("year"),
                     "mileage": item.get("mileage"),
-                    "latitude": None,
-                    "longitude": None,
+                    "latitude": item.get("latitude"),
+                    "longitude": item.get("longitude"),
                     "city": item.get("city") or city,
                     "url": item.get("url", ""),
                 }
@@ -88,6 +89,19 @@ class VendeTuNaveRustScraper(BaseScraper):
         )
         return listings
 
+    @staticmethod
+    def _cop_amount(price) -> Optional[str]:
+        """
+        Return the peso amount of the binary's typed price as a plain integer
+        string, or None for missing, on-request and non-COP prices.
+        """
+        if isinstance(price, dict):
+            amount = price.get("amount")
+            if amount is not None and price.get("currency") == "COP":
+                return str(amount)
+            return None
+        return price
+
     async def _run_binary(self, query: str) -> List[Dict]:
         """
         Execute the Rust binary in a thread pool so it does not block the
@@ -108,7 +122,14 @@ class VendeTuNaveRustScraper(BaseScraper):
             timeout=60,
         )
 
-        if result.returncode != 0:
+        if result.returncode == 3:
+            # Output is still valid; the binary flags degraded extraction
+            # (likely a site layout change) with a dedicated exit status.
+            logger.warning(
+                "vendetunave-scraper reported degraded extraction health. stderr: %s",
+                result.stderr,
+            )
+        elif result.returncode != 0:
             logger.warning(
                 "vendetunave-scraper exited with code %d. stderr: %s",
                 result.returncode,
@@ -121,6 +142,13 @@ class VendeTuNaveRustScraper(BaseScraper):
 
         try:
             data = json.loads(stdout)
+            if isinstance(data, dict) and isinstance(data.get("listings"), list):
+                logger.debug(
+                    "vendetunave-scraper visited %s result pages.",
+                    data.get("pages_visited"),
+                )
+                return data["listings"]
+            # Older binaries print a bare array.
             if isinstance(data, list):
                 return data
             logger.warning("Unexpected JSON output from binary: %s", type(data))
@@ -0,0 +1,174 @@
+//! Armored-vehicle (blindaje) detection.  Armored cars sell for far more
+//! than the same model without armor, so they are flagged with their
+//! protection level and can be excluded or compared separately.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::text::normalize;
+
+/// Normalized phrases that say a vehicle is armored.
+const ARMORED_MARKERS: &[&str] = &["blindado", "blindada", "blindados", "blindaje", "armored"];
+
+/// Normalized phrases that say it is not, checked first.  A "desblindado"
+/// car had its armor removed and prices like a regular one.
+const UNARMORED_MARKERS: &[&str] = &[
+    "no blindado",
+    "no blindada",
+    "no es blindado",
+    "no es blindada",
+    "sin blindaje",
+    "desblindado",
+    "desblindada",
+];
+
+/// What `--armored` does with armored listings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ArmoredMode {
+    /// Drop them.
+    Exclude,
+    /// Keep only them.
+    Only,
+    /// Move them to their own `armored_listings` array so they are compared
+    /// with each other rather than with regular cars.
+    Segment,
+}
+
+/// An armor statement found in listing text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Armor {
+    pub armored: bool,
+    /// Protection level as "3", "2+" or "3A"; roman numerals are converted.
+    pub level: Option<String>,
+}
+
+/// "nivel 3", "blindaje III", "blindado nivel 2+", "nivel de blindaje: 3A".
+fn level_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(?:blindaje|blindad[oa]s?|nivel)\s*(?:de\s+(?:blindaje|protecci[oó]n)\s*)?(?:nivel\s*)?:?\s*([1-7]|vii|vi|v|iv|iii|ii|i)(a)?(\+|\s*plus)?(?:[^\p{L}\p{N}+]|$)",
+        )
+        .expect("armor level pattern is valid")
+    })
+}
+
+/// Whether `text` says the vehicle is armored, and at which level.  `None`
+/// when armor is not mentioned at all.
+pub fn detect(text: &str) -> Option<Armor> {
+    // Without the dots `normalize` keeps, so "Blindado." still counts.
+    let padded = format!(" {} ", normalize(&text.replace('.', " ")));
+    let has = |markers: &[&str]| markers.iter().any(|m| padded.contains(&format!(" {m} ")));
+    if has(UNARMORED_MARKERS) {
+        return Some(Armor {
+            armored: false,
+            level: None,
+        });
+    }
+    if !has(ARMORED_MARKERS) {
+        return None;
+    }
+    Some(Armor {
+        armored: true,
+        level: level(text),
+    })
+}
+
+fn level(text: &str) -> Option<String> {
+    let cap = level_re().captures(text)?;
+    let raw = cap[1].to_lowercase();
+    let number = match raw.as_str() {
+        "i" => "1",
+        "ii" => "2",
+        "iii" => "3",
+        "iv" => "4",
+        "v" => "5",
+        "vi" => "6",
+        "vii" => "7",
+        digit => digit,
+    };
+    Some(format!("{number}{}", suffix(&cap)))
+}
+
+fn suffix(cap: &regex::Captures) -> String {
+    let mut suffix = String::new();
+    if cap.get(2).is_some() {
+        suffix.push('A');
+    }
+    if cap.get(3).is_some() {
+        suffix.push('+');
+    }
+    suffix
+}
+
+/// Look for armor in detail-page attributes such as "Blindado: Sí" or
+/// "Nivel de blindaje: III".
+pub fn from_attributes(attributes: &BTreeMap<String, String>) -> Option<Armor> {
+    attributes
+        .iter()
+        .filter(|(key, _)| normalize(key).contains("blind"))
+        .find_map(|(key, value)| match normalize(value).as_str() {
+            "no" | "false" | "0" => Some(Armor {
+                armored: false,
+                level: None,
+            }),
+            "si" | "yes" | "true" => Some(Armor {
+                armored: true,
+                level: None,
+            }),
+            _ => detect(&format!("{key} {value}")),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level_of(text: &str) -> Option<String> {
+        detect(text).and_then(|a| a.armored.then_some(a.level).flatten())
+    }
+
+    #[test]
+    fn test_levels() {
+        assert_eq!(
+            level_of("Toyota Prado blindado nivel 3").as_deref(),
+            Some("3")
+        );
+        assert_eq!(level_of("BLINDAJE III, full").as_deref(), Some("3"));
+        assert_eq!(level_of("Blindaje 2+ original").as_deref(), Some("2+"));
+        assert_eq!(
+            level_of("Camioneta blindada. Nivel de blindaje: 3A").as_deref(),
+            Some("3A")
+        );
+        assert_eq!(level_of("Blindado modelo 2019"), None);
+        assert_eq!(
+            detect("BMW X5 blindada"),
+            Some(Armor {
+                armored: true,
+                level: None
+            })
+        );
+    }
+
+    #[test]
+    fn test_not_armored() {
+        assert_eq!(detect("Mazda CX-5 2020 nivel 3 de equipamiento"), None);
+        assert_eq!(
+            detect("Fortuner desblindada").map(|a| a.armored),
+            Some(false)
+        );
+        assert_eq!(detect("No es blindado").map(|a| a.armored), Some(false));
+
+        let attributes = BTreeMap::from([("Blindado".to_string(), "No".to_string())]);
+        assert_eq!(from_attributes(&attributes).map(|a| a.armored), Some(false));
+        let attributes = BTreeMap::from([("Nivel de blindaje".to_string(), "III".to_string())]);
+        assert_eq!(
+            from_attributes(&attributes)
+                .and_then(|a| a.level)
+                .as_deref(),
+            Some("3")
+        );
+    }
+}
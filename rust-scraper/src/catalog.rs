@@ -0,0 +1,330 @@
+//! Make/model/trim/engine extraction from free-text listing titles, backed by
+//! the bundled `data/vehicle_catalog.json`.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::text::normalize;
+
+/// What the title parser recognised, with canonical catalog names.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CatalogMatch {
+    pub make: String,
+    pub model: Option<String>,
+    /// Whatever is left of the title after make, model, year, engine and
+    /// drivetrain words, e.g. "XEi".
+    pub trim: Option<String>,
+    pub engine_liters: Option<f32>,
+    /// 0.0–1.0; how sure we are of the make and model.
+    pub confidence: f32,
+}
+
+#[derive(Deserialize)]
+struct Catalog {
+    makes: Vec<Make>,
+}
+
+#[derive(Deserialize)]
+struct Make {
+    name: String,
+    aliases: Vec<String>,
+    models: Vec<Model>,
+}
+
+#[derive(Deserialize)]
+struct Model {
+    name: String,
+    aliases: Vec<String>,
+}
+
+/// Words that describe transmission, fuel or drivetrain rather than trim.
+const NON_TRIM_WORDS: &[&str] = &[
+    "at",
+    "mt",
+    "aut",
+    "automatico",
+    "automatica",
+    "mecanico",
+    "mecanica",
+    "manual",
+    "cvt",
+    "tiptronic",
+    "dsg",
+    "secuencial",
+    "gasolina",
+    "diesel",
+    "hibrido",
+    "electrico",
+    "gas",
+    "4x4",
+    "4x2",
+    "awd",
+    "4wd",
+    "2wd",
+    "fwd",
+    "modelo",
+    "mod",
+    "km",
+    "cc",
+    "l",
+    "turbo",
+];
+
+fn catalog() -> &'static Catalog {
+    static CATALOG: OnceLock<Catalog> = OnceLock::new();
+    CATALOG.get_or_init(|| {
+        serde_json::from_str(include_str!("../data/vehicle_catalog.json"))
+            .expect("bundled vehicle catalog is valid JSON")
+    })
+}
+
+/// One word of the title in its original spelling and normalized form.
+struct Token<'a> {
+    original: &'a str,
+    norm: String,
+}
+
+fn tokenize(title: &str) -> Vec<Token<'_>> {
+    title
+        .split(|c: char| c.is_whitespace() || matches!(c, '-' | '/' | ',' | '(' | ')' | '|'))
+        .map(|w| w.trim_matches('.'))
+        .filter(|w| !w.is_empty())
+        .map(|w| Token {
+            original: w,
+            norm: normalize(w),
+        })
+        .filter(|t| !t.norm.is_empty())
+        .collect()
+}
+
+fn alias_tokens(alias: &str) -> Vec<String> {
+    tokenize(alias).into_iter().map(|t| t.norm).collect()
+}
+
+/// First position at which `alias` occurs in `tokens`, at or after `from`.
+fn find_alias(tokens: &[Token], alias: &[String], from: usize) -> Option<usize> {
+    if alias.is_empty() || tokens.len() < alias.len() {
+        return None;
+    }
+    (from..=tokens.len() - alias.len()).find(|&i| {
+        alias
+            .iter()
+            .enumerate()
+            .all(|(j, a)| tokens[i + j].norm == *a)
+    })
+}
+
+/// Best (earliest, then longest) alias match for a set of candidates.
+/// Returns `(candidate index, start, token length)`.
+fn best_match<'c, I>(tokens: &[Token], candidates: I, from: usize) -> Option<(usize, usize, usize)>
+where
+    I: Iterator<Item = (usize, &'c [String])>,
+{
+    let mut best: Option<(usize, usize, usize)> = None;
+    for (idx, alias) in candidates {
+        if let Some(start) = find_alias(tokens, alias, from) {
+            let better = match best {
+                None => true,
+                Some((_, s, len)) => start < s || (start == s && alias.len() > len),
+            };
+            if better {
+                best = Some((idx, start, alias.len()));
+            }
+        }
+    }
+    best
+}
+
+/// Model aliases safe to match without a make in front of them: bare numbers
+/// ("3", "2008") and very short words ("rio", "fit") are too ambiguous.
+fn is_standalone_alias(alias: &[String]) -> bool {
+    let joined = alias.concat();
+    joined.len() >= 4 && !joined.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Parse a listing title such as "Toyota Corolla 2020 XEi 1.8 AT".
+///
+/// `brand_hint` is the marketplace's own brand field, used when the title
+/// does not name the make.  Returns `None` when no make can be identified.
+pub fn identify(title: &str, brand_hint: Option<&str>) -> Option<CatalogMatch> {
+    let catalog = catalog();
+    let tokens = tokenize(title);
+    let make_aliases: Vec<(usize, Vec<String>)> = catalog
+        .makes
+        .iter()
+        .enumerate()
+        .flat_map(|(i, m)| m.aliases.iter().map(move |a| (i, alias_tokens(a))))
+        .collect();
+
+    let title_make = best_match(
+        &tokens,
+        make_aliases.iter().map(|(i, a)| (*i, a.as_slice())),
+        0,
+    );
+    let hint_make = || {
+        let hint_tokens = tokenize(brand_hint?);
+        best_match(
+            &hint_tokens,
+            make_aliases.iter().map(|(i, a)| (*i, a.as_slice())),
+            0,
+        )
+        .map(|(i, _, _)| i)
+    };
+
+    let mut used = vec![false; tokens.len()];
+    let (make_idx, model, confidence) = match title_make {
+        Some((make_idx, start, len)) => {
+            used[start..start + len].iter_mut().for_each(|u| *u = true);
+            let model = match_model(&tokens, &catalog.makes[make_idx], start + len, false);
+            (make_idx, model, if model.is_some() { 0.95 } else { 0.5 })
+        }
+        None => match hint_make() {
+            Some(make_idx) => {
+                let model = match_model(&tokens, &catalog.makes[make_idx], 0, false);
+                (make_idx, model, if model.is_some() { 0.9 } else { 0.5 })
+            }
+            None => {
+                // No make anywhere: look for an unambiguous model name.
+                let found = catalog
+                    .makes
+                    .iter()
+                    .enumerate()
+                    .find_map(|(i, make)| match_model(&tokens, make, 0, true).map(|m| (i, m)));
+                let (make_idx, model) = found?;
+                (make_idx, Some(model), 0.8)
+            }
+        },
+    };
+
+    let make = &catalog.makes[make_idx];
+    if let Some((_, start, len)) = model {
+        used[start..start + len].iter_mut().for_each(|u| *u = true);
+    }
+
+    let mut engine_liters = None;
+    let mut trim = Vec::new();
+    for (token, used) in tokens.iter().zip(&used) {
+        if *used {
+            continue;
+        }
+        if engine_liters.is_none()
+            && let Some(liters) = parse_displacement(&token.norm)
+        {
+            engine_liters = Some(liters);
+            continue;
+        }
+        let is_year = token.norm.len() == 4
+            && token.norm.chars().all(|c| c.is_ascii_digit())
+            && matches!(&token.norm[..2], "19" | "20");
+        if is_year || NON_TRIM_WORDS.contains(&token.norm.as_str()) {
+            continue;
+        }
+        trim.push(token.original);
+    }
+
+    Some(CatalogMatch {
+        make: make.name.clone(),
+        model: model.map(|(i, _, _)| make.models[i].name.clone()),
+        trim: if trim.is_empty() {
+            None
+        } else {
+            Some(trim.join(" "))
+        },
+        engine_liters,
+        confidence,
+    })
+}
+
+/// Returns `(model index, start, token length)`.
+fn match_model(
+    tokens: &[Token],
+    make: &Make,
+    from: usize,
+    standalone_only: bool,
+) -> Option<(usize, usize, usize)> {
+    let aliases: Vec<(usize, Vec<String>)> = make
+        .models
+        .iter()
+        .enumerate()
+        .flat_map(|(i, m)| m.aliases.iter().map(move |a| (i, alias_tokens(a))))
+        .filter(|(_, a)| !standalone_only || is_standalone_alias(a))
+        .collect();
+    best_match(
+        tokens,
+        aliases.iter().map(|(i, a)| (*i, a.as_slice())),
+        from,
+    )
+}
+
+/// "1.8", "2.0t", "1.6l" → liters; "1600cc" → 1.6.
+fn parse_displacement(token: &str) -> Option<f32> {
+    if let Some(cc) = token.strip_suffix("cc") {
+        let cc: f32 = cc.parse().ok()?;
+        return (600.0..=8000.0)
+            .contains(&cc)
+            .then(|| (cc / 100.0).round() / 10.0);
+    }
+    let liters = token.trim_end_matches(['l', 't']);
+    if liters.len() != 3 || liters.as_bytes()[1] != b'.' {
+        return None;
+    }
+    let liters: f32 = liters.parse().ok()?;
+    (0.6..=8.0).contains(&liters).then_some(liters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify_full_title() {
+        let m = identify("Toyota Corolla 2020 XEi 1.8 AT", None).unwrap();
+        assert_eq!(m.make, "Toyota");
+        assert_eq!(m.model.as_deref(), Some("Corolla"));
+        assert_eq!(m.trim.as_deref(), Some("XEi"));
+        assert_eq!(m.engine_liters, Some(1.8));
+        assert_eq!(m.confidence, 0.95);
+    }
+
+    #[test]
+    fn test_identify_aliases() {
+        let m = identify("Chevy Spark GT 2015", None).unwrap();
+        assert_eq!(m.make, "Chevrolet");
+        assert_eq!(m.model.as_deref(), Some("Spark GT"));
+
+        let m = identify("Mazda3 Grand Touring 2.0 2019", None).unwrap();
+        assert_eq!(m.make, "Mazda");
+        assert_eq!(m.model.as_deref(), Some("3"));
+        assert_eq!(m.trim.as_deref(), Some("Grand Touring"));
+        assert_eq!(m.confidence, 0.8);
+
+        let m = identify("Mazda CX-5 Touring", None).unwrap();
+        assert_eq!(m.model.as_deref(), Some("CX-5"));
+    }
+
+    #[test]
+    fn test_identify_model_number_is_not_a_year() {
+        let m = identify("Peugeot 2008 Allure 2019 1600cc", None).unwrap();
+        assert_eq!(m.model.as_deref(), Some("2008"));
+        assert_eq!(m.trim.as_deref(), Some("Allure"));
+        assert_eq!(m.engine_liters, Some(1.6));
+    }
+
+    #[test]
+    fn test_identify_with_brand_hint() {
+        let m = identify("Duster Intens 4x4", Some("RENAULT")).unwrap();
+        assert_eq!(m.make, "Renault");
+        assert_eq!(m.model.as_deref(), Some("Duster"));
+        assert_eq!(m.trim.as_deref(), Some("Intens"));
+        assert_eq!(m.confidence, 0.9);
+    }
+
+    #[test]
+    fn test_identify_unknown() {
+        assert_eq!(identify("Carro usado en buen estado", None), None);
+        let m = identify("Kia modelo nuevo", None).unwrap();
+        assert_eq!(m.model, None);
+        assert_eq!(m.confidence, 0.5);
+    }
+}
@@ -0,0 +1,163 @@
+//! Calendar arithmetic on day counts since 1970-01-01 (UTC), enough for
+//! model-year caps, weekday rules and listing timestamps without pulling in
+//! a date crate.  Timestamps are plain Unix seconds (`i64`).
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const SECS_PER_DAY: i64 = 86_400;
+
+/// The current Unix time in seconds.
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// A proleptic Gregorian calendar date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// A date if `month` and `day` exist in `year`.
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Self> {
+        let date = Date { year, month, day };
+        (Date::from_days(date.days()) == date).then_some(date)
+    }
+
+    /// Parse an ISO `YYYY-MM-DD` date.  The year must have four digits, so
+    /// a day-first "12-03-2024" is not read as the year 12.
+    pub fn parse_iso(s: &str) -> Option<Self> {
+        let mut parts = s.trim().splitn(3, '-');
+        let year = parts
+            .next()
+            .filter(|y| y.len() == 4 && y.bytes().all(|b| b.is_ascii_digit()))?;
+        let year = year.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.get(..2)?.parse().ok()?;
+        Date::new(year, month, day)
+    }
+
+    /// Today in UTC.
+    pub fn today() -> Self {
+        Date::from_days(now().div_euclid(SECS_PER_DAY))
+    }
+
+    /// Days since 1970-01-01 (Howard Hinnant's `days_from_civil`).
+    pub fn days(self) -> i64 {
+        let y = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let m = i64::from(self.month);
+        let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+
+    /// The date `days` after 1970-01-01 (Howard Hinnant's `civil_from_days`).
+    /// Out-of-range inputs are not validated.
+    pub fn from_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+        Date { year, month, day }
+    }
+
+    /// Day of the week, 0 = Monday … 6 = Sunday.
+    pub fn weekday(self) -> u32 {
+        // 1970-01-01 was a Thursday.
+        (self.days() + 3).rem_euclid(7) as u32
+    }
+}
+
+/// Format a Unix timestamp as RFC 3339 UTC, e.g. `2026-10-12T14:00:00Z`.
+pub fn format_rfc3339(ts: i64) -> String {
+    let date = Date::from_days(ts.div_euclid(SECS_PER_DAY));
+    let secs = ts.rem_euclid(SECS_PER_DAY);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        date.year,
+        date.month,
+        date.day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Parse an ISO 8601 date or date-time into a Unix timestamp: `2024-03-12`,
+/// `2024-03-12 10:00`, `2024-05-01T10:00:00.123Z` or with a `±HH:MM`
+/// offset.  Values without an offset are taken as `default_offset_secs`
+/// east of UTC.
+pub fn parse_iso_datetime(s: &str, default_offset_secs: i64) -> Option<i64> {
+    let s = s.trim();
+    let date = Date::parse_iso(s.get(..10)?)?;
+    let rest = s[10..].trim_start_matches(['T', ' ']);
+
+    let (clock, offset) = match rest.find(['Z', 'z', '+', '-']) {
+        Some(i) => (&rest[..i], parse_offset(&rest[i..])?),
+        None => (rest, default_offset_secs),
+    };
+    let clock = clock.split('.').next().unwrap_or_default();
+    let mut fields = clock.split(':').filter(|f| !f.is_empty());
+    let mut next = |max: i64| -> Option<i64> {
+        match fields.next() {
+            Some(f) => f.parse().ok().filter(|v| (0..max).contains(v)),
+            None => Some(0),
+        }
+    };
+    let (h, m, sec) = (next(24)?, next(60)?, next(61)?);
+    Some(date.days() * SECS_PER_DAY + h * 3600 + m * 60 + sec - offset)
+}
+
+fn parse_offset(s: &str) -> Option<i64> {
+    if s.eq_ignore_ascii_case("z") {
+        return Some(0);
+    }
+    let sign = if s.starts_with('-') { -1 } else { 1 };
+    let digits: String = s[1..].chars().filter(char::is_ascii_digit).collect();
+    let (h, m) = digits.split_at(digits.len().min(2));
+    let h: i64 = h.parse().ok()?;
+    let m: i64 = if m.is_empty() { 0 } else { m.parse().ok()? };
+    Some(sign * (h * 3600 + m * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_round_trip_and_weekday() {
+        assert_eq!(Date::from_days(0), Date::new(1970, 1, 1).unwrap());
+        let leap = Date::new(2024, 2, 29).unwrap();
+        assert_eq!(leap.days(), 19_782);
+        assert_eq!(Date::from_days(leap.days()), leap);
+        assert_eq!(Date::new(2023, 2, 29), None);
+        assert_eq!(Date::from_days(19_723), Date::new(2024, 1, 1).unwrap());
+        assert_eq!(leap.weekday(), 3); // Thursday
+        assert_eq!(Date::parse_iso("2026-10-15"), Date::new(2026, 10, 15));
+        assert_eq!(Date::parse_iso("2026-13-01"), None);
+        assert_eq!(Date::parse_iso("12-03-2024"), None);
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let ts = parse_iso_datetime("2024-05-01T10:00:00Z", 0).unwrap();
+        assert_eq!(format_rfc3339(ts), "2024-05-01T10:00:00Z");
+        let ts = parse_iso_datetime("2024-05-01T05:00:00.5-05:00", 0).unwrap();
+        assert_eq!(format_rfc3339(ts), "2024-05-01T10:00:00Z");
+        let ts = parse_iso_datetime("2024-03-12", -5 * 3600).unwrap();
+        assert_eq!(format_rfc3339(ts), "2024-03-12T05:00:00Z");
+        assert_eq!(parse_iso_datetime("2024-03-12T25:00", 0), None);
+        assert_eq!(parse_iso_datetime("hace 3 días", 0), None);
+    }
+}
@@ -0,0 +1,571 @@
+//! Detail-page scraping for individual `/vehiculo/<id>/<slug>` listings.
+
+use std::collections::BTreeMap;
+
+use scraper::{ElementRef, Html, Selector};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::armor;
+use crate::extract_text_by_selectors;
+use crate::images;
+use crate::listing::{Extraction, Listing};
+use crate::mileage;
+use crate::next_data::{self, NextVehicle};
+use crate::plate;
+use crate::price::Price;
+use crate::published;
+use crate::seller;
+use crate::sources;
+use crate::sources::vendetunave::{NAME, extract_vehicle_id};
+use crate::structured::{self, StructuredItem};
+use crate::terms;
+use crate::year;
+
+/// A listing enriched with everything its detail page exposes.
+#[derive(Serialize, Debug)]
+pub struct DetailRecord {
+    #[serde(flatten)]
+    pub listing: Listing,
+    /// Every attribute shown on the spec sheet, keyed by its label.
+    pub attributes: BTreeMap<String, String>,
+    pub seller_name: Option<String>,
+}
+
+const DATE_KEYS: &[&str] = &[
+    "fechaPublicacion",
+    "fecha_publicacion",
+    "createdAt",
+    "created_at",
+    "fecha",
+];
+const SELLER_KEYS: &[&str] = &["vendedor", "nombreVendedor", "seller", "usuario"];
+
+/// The JSON-LD vehicle or product describing the page at `url`, rather
+/// than one in a list of related listings: the item linking to `url`, else
+/// the first that links nowhere.  Items that all link to other listings
+/// describe other cars, so none is taken.
+fn page_item(mut items: Vec<StructuredItem>, url: &str) -> Option<StructuredItem> {
+    let page = url.trim_end_matches('/');
+    let is_page = |item: &StructuredItem| {
+        item.url
+            .as_deref()
+            .map(|u| u.trim_end_matches('/'))
+            .is_some_and(|u| u == page || (u.starts_with('/') && page.ends_with(u)))
+    };
+    let at = items
+        .iter()
+        .position(is_page)
+        .or_else(|| items.iter().position(|item| item.url.is_none()))?;
+    Some(items.swap_remove(at))
+}
+
+/// Turn a CLI target (absolute URL, site path or bare vehicle id) into a URL
+/// on the site at `base_url`.  A bare id has no slug; `parse_detail` takes
+/// the full URL from the page.
+pub fn detail_url(base_url: &str, target: &str) -> String {
+    let target = target.trim();
+    if target.starts_with("http") {
+        target.to_string()
+    } else if target.starts_with('/') {
+        format!("{base_url}{target}")
+    } else {
+        format!("{base_url}/vehiculo/{target}")
+    }
+}
+
+/// The page's canonical URL when it names the same vehicle as `url`, so a
+/// listing fetched by bare id is recorded under its `/vehiculo/<id>/<slug>`.
+fn canonical_url(document: &Html, url: &str, base_url: &str) -> String {
+    let id = extract_vehicle_id(url);
+    [
+        "link[rel='canonical'][href]",
+        "meta[property='og:url'][content]",
+    ]
+    .iter()
+    .filter_map(|selector| {
+        let sel = Selector::parse(selector).ok()?;
+        let element = document.select(&sel).next()?;
+        let href = element
+            .value()
+            .attr("href")
+            .or(element.value().attr("content"))?;
+        sources::resolve_link(base_url, href.trim())
+    })
+    .find(|canonical| id.is_some() && extract_vehicle_id(canonical) == id)
+    .unwrap_or_else(|| url.to_string())
+}
+
+/// Parse a detail page.  The `__NEXT_DATA__` vehicle object is preferred; the
+/// rendered HTML is only used when it is missing.  schema.org JSON-LD and
+/// OpenGraph tags then fill gaps in the former and correct the latter.
+pub fn parse_detail(html: &str, url: &str, base_url: &str) -> Option<DetailRecord> {
+    let document = Html::parse_document(html);
+    let url = &canonical_url(&document, url, base_url);
+    let json_ld = page_item(structured::json_ld_items(&document), url);
+    let open_graph = structured::open_graph(&document);
+
+    let mut record = next_data::extract_detail_vehicle(&document)
+        .and_then(|vehicle| parse_next_data_detail(vehicle, url))
+        .or_else(|| parse_html_detail(&document, url, base_url))
+        .or_else(|| {
+            let (item, source) = match (&json_ld, &open_graph) {
+                (Some(item), _) => (item, Extraction::JsonLd),
+                (None, Some(item)) => (item, Extraction::OpenGraph),
+                (None, None) => return None,
+            };
+            let listing = structured::into_listing(item, NAME, source)?;
+            Some(DetailRecord {
+                listing,
+                attributes: BTreeMap::new(),
+                seller_name: None,
+            })
+        })?;
+
+    let overwrite = record.listing.extraction == Extraction::HtmlCard;
+    for (item, source, overwrite) in [
+        (json_ld, Extraction::JsonLd, overwrite),
+        (open_graph, Extraction::OpenGraph, false),
+    ] {
+        if let Some(item) = item {
+            merge_structured(&mut record, item, source, overwrite);
+        }
+    }
+    record.listing.url = url.to_string();
+    record.listing.id = record.listing.id.take().or_else(|| extract_vehicle_id(url));
+    (
+        record.listing.plate_last_digit,
+        record.listing.registration_city,
+    ) = plate::from_attributes(&record.attributes);
+    if let Some(armor) = armor::from_attributes(&record.attributes) {
+        record.listing.armored = Some(armor.armored);
+        record.listing.armor_level = armor.level;
+    }
+    terms::from_attributes(&record.attributes).fill(&mut record.listing);
+    if record.listing.seller_type.is_none() {
+        record.listing.seller_type = record
+            .seller_name
+            .as_deref()
+            .and_then(seller::classify)
+            .or_else(|| {
+                record
+                    .attributes
+                    .iter()
+                    .filter(|(k, _)| k.to_lowercase().contains("vendedor"))
+                    .find_map(|(_, v)| seller::classify(v))
+            });
+    }
+    Some(record)
+}
+
+/// Apply one page-level structured item to `record`, keeping the requested
+/// URL and appending any photos the gallery did not already have.
+fn merge_structured(
+    record: &mut DetailRecord,
+    item: StructuredItem,
+    source: Extraction,
+    overwrite: bool,
+) {
+    let item = StructuredItem { url: None, ..item };
+    structured::apply(&mut record.listing, &item, source, overwrite);
+}
+
+fn parse_next_data_detail(vehicle: Value, url: &str) -> Option<DetailRecord> {
+    let attributes = scalar_attributes(&vehicle);
+    let published_raw = DATE_KEYS.iter().find_map(|k| attributes.get(*k).cloned());
+    let seller_name = SELLER_KEYS.iter().find_map(|k| match vehicle.get(*k)? {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Object(o) => ["nombre", "name"]
+            .iter()
+            .find_map(|n| o.get(*n)?.as_str().map(|s| s.trim().to_string())),
+        _ => None,
+    });
+
+    let mut listing = serde_json::from_value::<NextVehicle>(vehicle)
+        .ok()?
+        .into_listing()?;
+    listing.url = url.to_string();
+    listing.published_raw = listing.published_raw.or(published_raw);
+
+    Some(DetailRecord {
+        listing,
+        attributes,
+        seller_name,
+    })
+}
+
+fn parse_html_detail(document: &Html, url: &str, base_url: &str) -> Option<DetailRecord> {
+    let root = document.root_element();
+    let title = extract_text_by_selectors(&root, &["h1", "[class*='title']", "[class*='titulo']"]);
+    if title.is_empty() {
+        return None;
+    }
+
+    let attributes = html_attributes(&root);
+    let attr = |keys: &[&str]| keys.iter().find_map(|k| attributes.get(*k).cloned());
+
+    let model_year = year::extract(
+        &title,
+        attr(&["año", "ano", "modelo"])
+            .map(|v| format!("modelo: {v}"))
+            .as_deref(),
+    );
+
+    let mut listing = Listing::new(title, url.to_string(), NAME, Extraction::HtmlCard);
+    listing.id = extract_vehicle_id(url);
+    listing.price = Price::parse(&extract_text_by_selectors(
+        &root,
+        &["[class*='price']", "[class*='precio']", "[data-price]"],
+    ));
+    listing.year = model_year.map(|y| y.year);
+    listing.year_confidence = model_year.map(|y| y.confidence);
+    let odometer = ["kilometraje", "kilómetros", "kilometros", "millaje"]
+        .iter()
+        .find_map(|k| {
+            let label = if *k == "millaje" {
+                "millaje"
+            } else {
+                "kilometraje"
+            };
+            mileage::extract(&format!("{label}: {}", attributes.get(*k)?))
+        });
+    if let Some(reading) = odometer {
+        listing.mileage = Some(reading.km);
+        listing.mileage_unit = Some(reading.unit);
+    }
+    listing.city = attr(&["ciudad", "ubicación", "ubicacion"]);
+    listing.brand = attr(&["marca"]);
+    listing.fuel = attr(&["combustible"]);
+    listing.transmission = attr(&["transmisión", "transmision"]);
+    listing.condition = attr(&["condición", "condicion", "estado"]);
+    listing.description = non_empty(extract_text_by_selectors(
+        &root,
+        &["[class*='descripcion']", "[class*='description']"],
+    ))
+    .or_else(|| meta_content(document, "meta[name='description']"));
+
+    listing.images = html_images(document, base_url);
+    listing.image_url = listing.images.first().cloned();
+    listing.published_raw = Selector::parse("time[datetime]")
+        .ok()
+        .and_then(|sel| {
+            document
+                .select(&sel)
+                .next()
+                .and_then(|t| t.value().attr("datetime").map(str::to_string))
+        })
+        .or_else(|| attr(&["publicado", "fecha de publicación", "fecha"]))
+        .or_else(|| {
+            let text = extract_text_by_selectors(
+                &root,
+                &["[class*='fecha']", "[class*='publicad']", "[class*='date']"],
+            );
+            published::find_phrase(&text).map(str::to_string)
+        });
+    listing.record_provenance(Extraction::HtmlCard);
+
+    let seller_name = non_empty(extract_text_by_selectors(
+        &root,
+        &["[class*='vendedor']", "[class*='seller']"],
+    ));
+
+    Some(DetailRecord {
+        listing,
+        attributes,
+        seller_name,
+    })
+}
+
+/// Every non-empty scalar field of a JSON object, stringified.
+fn scalar_attributes(value: &Value) -> BTreeMap<String, String> {
+    let Some(object) = value.as_object() else {
+        return BTreeMap::new();
+    };
+    object
+        .iter()
+        .filter_map(|(k, v)| {
+            let text = match v {
+                Value::String(s) => s.trim().to_string(),
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                _ => return None,
+            };
+            (!text.is_empty()).then(|| (k.clone(), text))
+        })
+        .collect()
+}
+
+/// Spec-sheet rows rendered as `<dt>/<dd>` pairs or two-cell table rows,
+/// keyed by the lowercased label.
+fn html_attributes(root: &ElementRef) -> BTreeMap<String, String> {
+    let mut attributes = BTreeMap::new();
+    let mut insert = |label: ElementRef, value: ElementRef| {
+        let key = element_text(&label)
+            .trim_end_matches(':')
+            .trim()
+            .to_lowercase();
+        let value = element_text(&value);
+        if !key.is_empty() && !value.is_empty() {
+            attributes.entry(key).or_insert(value);
+        }
+    };
+
+    if let Ok(dt_sel) = Selector::parse("dt") {
+        for dt in root.select(&dt_sel) {
+            if let Some(dd) = dt
+                .next_siblings()
+                .filter_map(ElementRef::wrap)
+                .find(|e| e.value().name() == "dd")
+            {
+                insert(dt, dd);
+            }
+        }
+    }
+
+    if let (Ok(tr_sel), Ok(cell_sel)) = (Selector::parse("tr"), Selector::parse("th, td")) {
+        for tr in root.select(&tr_sel) {
+            let cells: Vec<_> = tr.select(&cell_sel).collect();
+            if let [label, value] = cells[..] {
+                insert(label, value);
+            }
+        }
+    }
+
+    attributes
+}
+
+/// Every absolute image URL on the page, including lazy-loaded and
+/// `srcset` ones, with `og:image` first.  Root-relative ones are resolved
+/// against `base_url`.
+fn html_images(document: &Html, base_url: &str) -> Vec<String> {
+    let mut images: Vec<String> = meta_content(document, "meta[property='og:image']")
+        .into_iter()
+        .collect();
+    let absolute = |src: &str| sources::resolve_link(base_url, src);
+    for src in images::collect(&document.root_element(), absolute) {
+        if !images.contains(&src) {
+            images.push(src);
+        }
+    }
+    images
+}
+
+fn meta_content(document: &Html, selector: &str) -> Option<String> {
+    let sel = Selector::parse(selector).ok()?;
+    let content = document.select(&sel).next()?.value().attr("content")?;
+    non_empty(content.trim().to_string())
+}
+
+fn element_text(element: &ElementRef) -> String {
+    element
+        .text()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn non_empty(s: String) -> Option<String> {
+    if s.is_empty() { None } else { Some(s) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_URL: &str = "https://www.vendetunave.co";
+
+    #[test]
+    fn test_detail_url() {
+        assert_eq!(
+            detail_url(BASE_URL, "4521"),
+            "https://www.vendetunave.co/vehiculo/4521"
+        );
+        assert_eq!(
+            detail_url(BASE_URL, "/vehiculo/4521/mazda-3"),
+            "https://www.vendetunave.co/vehiculo/4521/mazda-3"
+        );
+        assert_eq!(
+            detail_url(BASE_URL, "https://www.vendetunave.co/vehiculo/1/x"),
+            "https://www.vendetunave.co/vehiculo/1/x"
+        );
+    }
+
+    #[test]
+    fn test_parse_detail_next_data() {
+        let html = r#"<html><body><script id="__NEXT_DATA__" type="application/json">
+            {"props":{"pageProps":{"data":{"vehicle":{
+                "id":4521,"title":"Mazda 3 Grand Touring","precio":72000000,
+                "descripcion":"Único dueño, mantenimientos en concesionario.",
+                "cilindraje":"2.0","color":"Rojo","fechaPublicacion":"2024-03-12",
+                "nameImage":"cover","extension":"jpg",
+                "imagenes":[{"nameImage":"a1","extension":"jpg"},"https://cdn.example/b2.jpg"],
+                "vendedor":{"nombre":"Autos La 80"}
+            }}}}}
+        </script></body></html>"#;
+        let url = "https://www.vendetunave.co/vehiculo/4521/mazda-3-grand-touring";
+        let record = parse_detail(html, url, BASE_URL).unwrap();
+
+        assert_eq!(record.listing.title, "Mazda 3 Grand Touring");
+        assert_eq!(record.listing.extraction, Extraction::NextData);
+        assert_eq!(
+            record.attributes.get("color").map(String::as_str),
+            Some("Rojo")
+        );
+        assert_eq!(
+            record.attributes.get("cilindraje").map(String::as_str),
+            Some("2.0")
+        );
+        assert_eq!(record.listing.published_raw.as_deref(), Some("2024-03-12"));
+        assert_eq!(record.seller_name.as_deref(), Some("Autos La 80"));
+        assert_eq!(
+            record.listing.images,
+            vec![
+                "https://static.vendetunave.co/images/vehiculos/cover.jpg",
+                "https://static.vendetunave.co/images/vehiculos/a1.jpg",
+                "https://cdn.example/b2.jpg",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_detail_html_fallback() {
+        let html = r#"<html><head>
+                <meta property="og:image" content="https://cdn.example/cover.jpg">
+            </head><body>
+                <h1>Renault Duster 2018</h1>
+                <span class="precio">$48.000.000</span>
+                <dl><dt>Año:</dt><dd>2018</dd><dt>Kilometraje</dt><dd>61.000</dd></dl>
+                <table><tr><th>Ciudad</th><td>Cali</td></tr></table>
+                <div class="descripcion">Perfecto estado</div>
+                <img data-src="https://cdn.example/1.jpg" src="placeholder.gif">
+                <time datetime="2024-05-01T10:00:00Z">hace 3 días</time>
+            </body></html>"#;
+        let record = parse_detail(
+            html,
+            "https://www.vendetunave.co/vehiculo/77/duster",
+            BASE_URL,
+        )
+        .unwrap();
+
+        assert_eq!(record.listing.extraction, Extraction::HtmlCard);
+        assert_eq!(record.listing.id.as_deref(), Some("77"));
+        assert_eq!(record.listing.price.amount, Some(48_000_000));
+        assert_eq!(record.listing.year, Some(2018));
+        assert_eq!(record.listing.mileage, Some(61000));
+        assert_eq!(record.listing.city.as_deref(), Some("Cali"));
+        assert_eq!(
+            record.listing.description.as_deref(),
+            Some("Perfecto estado")
+        );
+        assert_eq!(
+            record.listing.published_raw.as_deref(),
+            Some("2024-05-01T10:00:00Z")
+        );
+        assert_eq!(
+            record.listing.images,
+            vec!["https://cdn.example/cover.jpg", "https://cdn.example/1.jpg"]
+        );
+    }
+
+    #[test]
+    fn test_parse_detail_structured_data_overrides_html() {
+        let html = r#"<html><head>
+                <meta property="og:description" content="Full equipo">
+                <script type="application/ld+json">
+                {"@context":"https://schema.org","@type":"Car","name":"Kia Sportage",
+                 "vehicleModelDate":"2021","image":"https://cdn.example/ld.jpg",
+                 "offers":{"@type":"Offer","price":"89000000","priceCurrency":"COP"}}
+                </script>
+            </head><body>
+                <h1>Kia Sportage 2022</h1>
+                <table><tr><th>Ciudad</th><td>Pereira</td></tr></table>
+            </body></html>"#;
+        let record =
+            parse_detail(html, "https://www.vendetunave.co/vehiculo/9/kia", BASE_URL).unwrap();
+        let listing = &record.listing;
+
+        assert_eq!(listing.title, "Kia Sportage");
+        assert_eq!(listing.year, Some(2021));
+        assert_eq!(listing.price.amount, Some(89_000_000));
+        assert_eq!(listing.description.as_deref(), Some("Full equipo"));
+        assert_eq!(listing.url, "https://www.vendetunave.co/vehiculo/9/kia");
+        assert_eq!(listing.provenance["year"], Extraction::JsonLd);
+        assert_eq!(listing.provenance["city"], Extraction::HtmlCard);
+        assert_eq!(listing.provenance["description"], Extraction::OpenGraph);
+        assert_eq!(record.listing.images, vec!["https://cdn.example/ld.jpg"]);
+    }
+
+    #[test]
+    fn test_parse_detail_picks_the_page_json_ld_item() {
+        let html = r#"<html><head>
+                <script type="application/ld+json">
+                {"@context":"https://schema.org","@type":"Organization","name":"VendeTuNave"}
+                </script>
+                <script type="application/ld+json">
+                {"@context":"https://schema.org","@type":"ItemList","itemListElement":[
+                  {"@type":"ListItem","item":{"@type":"Car","name":"Mazda 2",
+                   "url":"/vehiculo/12/mazda-2","vehicleModelDate":"2016"}}]}
+                </script>
+                <script type="application/ld+json">
+                {"@context":"https://schema.org","@type":"Car","name":"Kia Sportage",
+                 "url":"https://www.vendetunave.co/vehiculo/9/kia","vehicleModelDate":"2021"}
+                </script>
+            </head><body></body></html>"#;
+        let record =
+            parse_detail(html, "https://www.vendetunave.co/vehiculo/9/kia", BASE_URL).unwrap();
+        assert_eq!(record.listing.title, "Kia Sportage");
+        assert_eq!(record.listing.year, Some(2021));
+    }
+
+    #[test]
+    fn test_parse_detail_ignores_related_listings() {
+        let html = r#"<html><head>
+                <script type="application/ld+json">
+                {"@context":"https://schema.org","@type":"ItemList","itemListElement":[
+                  {"@type":"ListItem","item":{"@type":"Car","name":"Mazda 2",
+                   "url":"/vehiculo/12/mazda-2","vehicleModelDate":"2016",
+                   "offers":{"@type":"Offer","price":"38000000","priceCurrency":"COP"}}},
+                  {"@type":"ListItem","item":{"@type":"Car","name":"Chevrolet Spark",
+                   "url":"https://www.vendetunave.co/vehiculo/13/spark","vehicleModelDate":"2015"}}]}
+                </script>
+            </head><body>
+                <h1>Renault Duster 2018</h1>
+                <span class="precio">$48.000.000</span>
+            </body></html>"#;
+        let record = parse_detail(
+            html,
+            "https://www.vendetunave.co/vehiculo/77/duster",
+            BASE_URL,
+        )
+        .unwrap();
+        assert_eq!(record.listing.title, "Renault Duster 2018");
+        assert_eq!(record.listing.year, Some(2018));
+        assert_eq!(record.listing.price.amount, Some(48_000_000));
+    }
+
+    #[test]
+    fn test_parse_detail_follows_the_base_url() {
+        let html = r#"<html><head>
+                <link rel="canonical" href="/vehiculo/77/renault-duster">
+            </head><body>
+                <h1>Renault Duster 2018</h1>
+                <img src="/images/duster.jpg">
+            </body></html>"#;
+        let base_url = "https://staging.vendetunave.co";
+        let url = detail_url(base_url, "77");
+        assert_eq!(url, "https://staging.vendetunave.co/vehiculo/77");
+
+        let record = parse_detail(html, &url, base_url).unwrap();
+        assert_eq!(
+            record.listing.url,
+            "https://staging.vendetunave.co/vehiculo/77/renault-duster"
+        );
+        assert_eq!(record.listing.source, NAME);
+        assert_eq!(
+            record.listing.images,
+            vec!["https://staging.vendetunave.co/images/duster.jpg"]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Post-extraction filters selected on the command line.
+
+use crate::armor::ArmoredMode;
+use crate::dates;
+use crate::listing::Listing;
+use crate::pico_y_placa::PicoYPlacaFilter;
+use crate::seller::SellerType;
+use crate::tags;
+use crate::terms::PriceType;
+
+/// Every active filter; a listing is kept only if it passes all of them.
+#[derive(Debug, Default)]
+pub struct Filters {
+    pub pico_y_placa: Vec<PicoYPlacaFilter>,
+    /// Keep only listings known to be from this kind of seller.
+    pub seller_type: Option<SellerType>,
+    /// Drop listings published longer ago than this; listings without a
+    /// known publication time are kept.
+    pub max_age_days: Option<u32>,
+    /// Keep only listings known to accept financing.
+    pub accepts_financing: bool,
+    /// Keep only listings known to accept a trade-in.
+    pub accepts_trade_in: bool,
+    /// Keep only listings whose price type is one of these, if any are given.
+    pub price_types: Vec<PriceType>,
+    /// Drop armored listings, or everything else.  `Segment` keeps both; the
+    /// caller splits them.
+    pub armored: Option<ArmoredMode>,
+    /// Tags every kept listing must carry.
+    pub required_tags: Vec<String>,
+    /// The time ages are measured from, as Unix seconds.
+    pub now: i64,
+}
+
+impl Filters {
+    pub fn accepts(&self, listing: &Listing) -> bool {
+        let published = listing
+            .published_at
+            .as_deref()
+            .and_then(|ts| dates::parse_iso_datetime(ts, 0));
+        let fresh = match (self.max_age_days, published) {
+            (Some(days), Some(ts)) => self.now - ts <= i64::from(days) * dates::SECS_PER_DAY,
+            _ => true,
+        };
+
+        let armored = listing.armored == Some(true);
+        let armor_ok = match self.armored {
+            Some(ArmoredMode::Exclude) => !armored,
+            Some(ArmoredMode::Only) => armored,
+            Some(ArmoredMode::Segment) | None => true,
+        };
+
+        fresh
+            && armor_ok
+            && (!self.accepts_financing || listing.accepts_financing == Some(true))
+            && (!self.accepts_trade_in || listing.accepts_trade_in == Some(true))
+            && (self.price_types.is_empty()
+                || listing
+                    .price_type
+                    .is_some_and(|t| self.price_types.contains(&t)))
+            && self.required_tags.iter().all(|t| tags::has_tag(listing, t))
+            && self
+                .seller_type
+                .is_none_or(|wanted| listing.seller_type == Some(wanted))
+            && !self
+                .pico_y_placa
+                .iter()
+                .any(|f| f.restricts(listing.plate_last_digit))
+    }
+}
@@ -0,0 +1,418 @@
+//! Colombian municipality gazetteer, backed by the bundled
+//! `data/municipalities.json` (DANE DIVIPOLA codes).
+//!
+//! Resolves free-text locations such as "MEDELLIN", "Medellín, Antioquia" or
+//! "Envigado - Antioquia" to a canonical municipality.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::text::normalize;
+
+/// A resolved municipality.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Place {
+    /// Five-digit DANE DIVIPOLA code, e.g. "05001".
+    pub dane_code: String,
+    pub city: String,
+    pub department: String,
+    pub metro_area: Option<String>,
+    /// How the location text was matched.
+    pub matched: Match,
+    /// Municipality centroid as `(latitude, longitude)`.
+    #[serde(skip)]
+    pub centroid: (f64, f64),
+}
+
+/// How a location was resolved, from most to least certain.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Match {
+    /// The municipality's own name.
+    Name,
+    /// A listed alias, e.g. "Santiago de Cali".
+    Alias,
+    /// Only a department with a single municipality, e.g. Bogotá D.C.
+    Department,
+    /// The closest spelling within a few edits, e.g. "Medelin".
+    Fuzzy,
+}
+
+impl Place {
+    /// Whether the text names the municipality as written.  Free-text
+    /// matchers only accept these, as a near miss there is as likely an
+    /// ordinary word.
+    pub fn is_exact(&self) -> bool {
+        self.matched != Match::Fuzzy
+    }
+}
+
+#[derive(Deserialize)]
+struct Gazetteer {
+    departments: Vec<Department>,
+    municipalities: Vec<Municipality>,
+}
+
+#[derive(Deserialize)]
+struct Department {
+    name: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    /// Whether every municipality of the department is listed.
+    #[serde(default)]
+    complete: bool,
+}
+
+#[derive(Deserialize)]
+struct Municipality {
+    dane: String,
+    name: String,
+    department: String,
+    lat: f64,
+    lon: f64,
+    metro_area: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+}
+
+impl Municipality {
+    fn place(&self, matched: Match) -> Place {
+        Place {
+            dane_code: self.dane.clone(),
+            city: self.name.clone(),
+            department: self.department.clone(),
+            metro_area: self.metro_area.clone(),
+            matched,
+            centroid: (self.lat, self.lon),
+        }
+    }
+}
+
+struct Index {
+    gazetteer: Gazetteer,
+    /// Every normalized municipality name and alias with its municipality,
+    /// in list order.
+    names: Vec<(String, usize)>,
+    /// Every normalized department name and alias with its department,
+    /// longest first so "norte de santander" beats "santander".
+    departments: Vec<(String, usize)>,
+    /// The department of each municipality, by position.
+    department_of: Vec<Option<usize>>,
+}
+
+impl Index {
+    /// Whether municipality `i` lies in department `d`.
+    fn in_department(&self, i: usize, d: usize) -> bool {
+        self.department_of[i] == Some(d)
+    }
+
+    /// Whether misspellings of municipality `i` may be corrected, i.e. its
+    /// department is listed completely.
+    fn correctable(&self, i: usize) -> bool {
+        self.department_of[i].is_some_and(|d| self.gazetteer.departments[d].complete)
+    }
+
+    fn department(&self, part: &str) -> Option<usize> {
+        self.departments
+            .iter()
+            .find(|(n, _)| n == part)
+            .map(|(_, d)| *d)
+    }
+}
+
+fn index() -> &'static Index {
+    static INDEX: OnceLock<Index> = OnceLock::new();
+    INDEX.get_or_init(|| {
+        let gazetteer: Gazetteer =
+            serde_json::from_str(include_str!("../data/municipalities.json"))
+                .expect("bundled gazetteer is valid JSON");
+        let names: Vec<(String, usize)> = gazetteer
+            .municipalities
+            .iter()
+            .enumerate()
+            .flat_map(|(i, m)| {
+                std::iter::once(normalize(&m.name))
+                    .chain(m.aliases.iter().map(|a| normalize(a)))
+                    .map(move |n| (n, i))
+            })
+            .collect();
+        let mut departments: Vec<(String, usize)> = gazetteer
+            .departments
+            .iter()
+            .enumerate()
+            .flat_map(|(d, dep)| {
+                std::iter::once(normalize(&dep.name))
+                    .chain(dep.aliases.iter().map(|a| normalize(a)))
+                    .map(move |n| (n, d))
+            })
+            .collect();
+        departments.sort_by_key(|(n, _)| std::cmp::Reverse(n.len()));
+        let department_of = gazetteer
+            .municipalities
+            .iter()
+            .map(|m| {
+                let name = normalize(&m.department);
+                gazetteer
+                    .departments
+                    .iter()
+                    .position(|d| normalize(&d.name) == name)
+            })
+            .collect();
+        Index {
+            gazetteer,
+            names,
+            departments,
+            department_of,
+        }
+    })
+}
+
+/// Split the department off a location such as "Chinchiná, Caldas" or
+/// "Envigado Antioquia", leaving the parts that may name the municipality.
+/// The first part is never taken for the department, so "Caldas" alone is
+/// still the town, and neither is a whole name such as "Puerto Boyacá".
+fn split_department(index: &Index, raw: &str) -> (Vec<String>, Option<usize>) {
+    let mut parts: Vec<String> = raw
+        .split([',', '-', '/', '(', ')', '|'])
+        .map(normalize)
+        .filter(|p| !p.is_empty())
+        .collect();
+
+    if let Some(at) = parts
+        .iter()
+        .skip(1)
+        .rposition(|p| index.department(p).is_some())
+    {
+        let department = index.department(&parts.remove(at + 1));
+        return (parts, department);
+    }
+    if let [only] = parts.as_slice()
+        && !index.names.iter().any(|(n, _)| n == only)
+        && let Some((prefix, d)) = index.departments.iter().find_map(|(n, d)| {
+            let prefix = only.strip_suffix(n.as_str())?.strip_suffix(' ')?;
+            Some((prefix.to_string(), *d))
+        })
+    {
+        return (vec![prefix], Some(d));
+    }
+    (parts, None)
+}
+
+/// Resolve a location string to a municipality.
+///
+/// A department named after the municipality, as in "Envigado, Antioquia",
+/// limits the candidates to that department and is never itself read as a
+/// town.  Tries, in order: an exact name or alias match on the remaining
+/// text or on any of its comma/dash separated parts, then a name contained
+/// in it, then the closest spelling within a few edits.  When several
+/// municipalities match, as in "Belén, Medellín", the one listed first wins;
+/// the bundled list puts the larger towns first.
+pub fn resolve(raw: &str) -> Option<Place> {
+    let index = index();
+    let found = |i: usize, matched| Some(index.gazetteer.municipalities[i].place(matched));
+    // Whether `name`, from the index, is municipality `i`'s own name.
+    let by_name = |name: &str, i: usize| {
+        if normalize(&index.gazetteer.municipalities[i].name) == name {
+            Match::Name
+        } else {
+            Match::Alias
+        }
+    };
+
+    let (parts, department) = split_department(index, raw);
+    if parts.is_empty() && department.is_none() {
+        return None;
+    }
+    let candidate = |i: usize| department.is_none_or(|d| index.in_department(i, d));
+    let whole = parts.join(" ");
+    let parts: Vec<String> = std::iter::once(whole.clone()).chain(parts).collect();
+
+    let exact = parts
+        .iter()
+        .flat_map(|part| index.names.iter().filter(move |(n, _)| n == part))
+        .filter(|(_, i)| candidate(*i))
+        .min_by_key(|(_, i)| *i);
+    if let Some((n, i)) = exact {
+        return found(*i, by_name(n, *i));
+    }
+
+    let padded = format!(" {whole} ");
+    let contained: Vec<&(String, usize)> = index
+        .names
+        .iter()
+        .filter(|(n, i)| padded.contains(&format!(" {n} ")) && candidate(*i))
+        .collect();
+    // "villa del rosario" also contains "rosario"; only the longer name
+    // counts.
+    let within_longer = |n: &str| {
+        contained
+            .iter()
+            .any(|(m, _)| m.len() > n.len() && format!(" {m} ").contains(&format!(" {n} ")))
+    };
+    if let Some((n, i)) = contained
+        .iter()
+        .filter(|(n, _)| !within_longer(n))
+        .min_by_key(|(_, i)| *i)
+    {
+        return found(*i, by_name(n, *i));
+    }
+
+    for part in &parts {
+        // A short word is as likely a different word as a typo.
+        let max_distance = match part.chars().count() {
+            0..=4 => continue,
+            5..=8 => 1,
+            _ => 2,
+        };
+        let best = index
+            .names
+            .iter()
+            .filter(|(_, i)| candidate(*i) && index.correctable(*i))
+            .map(|(n, i)| (levenshtein(n, part), *i))
+            .filter(|(d, _)| *d <= max_distance)
+            .min();
+        if let Some((_, i)) = best {
+            return found(i, Match::Fuzzy);
+        }
+    }
+
+    // A department with a single municipality, such as Bogotá D.C., names
+    // the town as well: "Suba - Bogotá D.C." is in Bogotá.
+    let department = department?;
+    let mut municipalities =
+        (0..index.gazetteer.municipalities.len()).filter(|i| index.in_department(*i, department));
+    match (municipalities.next(), municipalities.next()) {
+        (Some(i), None) => found(i, Match::Department),
+        _ => None,
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_variants() {
+        for raw in [
+            "MEDELLIN",
+            "Medellín, Antioquia",
+            "medellin - antioquia",
+            "Medellín Antioquia",
+        ] {
+            let place = resolve(raw).unwrap();
+            assert_eq!(place.city, "Medellín", "{raw}");
+            assert_eq!(place.dane_code, "05001");
+        }
+
+        let place = resolve("Envigado - Antioquia").unwrap();
+        assert_eq!(place.city, "Envigado");
+        assert_eq!(place.department, "Antioquia");
+        assert_eq!(place.metro_area.as_deref(), Some("Valle de Aburrá"));
+    }
+
+    #[test]
+    fn test_centroids_are_in_colombia() {
+        for m in &index().gazetteer.municipalities {
+            assert!((-4.3..=13.5).contains(&m.lat), "{}", m.name);
+            assert!((-82.0..=-66.8).contains(&m.lon), "{}", m.name);
+        }
+        assert_eq!(resolve("Bogotá").unwrap().centroid, (4.711, -74.0721));
+    }
+
+    #[test]
+    fn test_resolve_aliases_and_contained_names() {
+        assert_eq!(resolve("Bogotá D.C.").unwrap().dane_code, "11001");
+        assert_eq!(resolve("Bogotá D.C.").unwrap().matched, Match::Alias);
+        assert_eq!(resolve("Medellín, Antioquia").unwrap().matched, Match::Name);
+        assert_eq!(resolve("Santiago de Cali").unwrap().city, "Cali");
+        assert_eq!(
+            resolve("Barrio Laureles Medellin").unwrap().city,
+            "Medellín"
+        );
+        assert_eq!(
+            resolve("Villa del Rosario").unwrap().city,
+            "Villa del Rosario"
+        );
+    }
+
+    #[test]
+    fn test_resolve_unknown() {
+        assert_eq!(resolve(""), None);
+        assert_eq!(resolve("Ciudad Gótica"), None);
+    }
+
+    #[test]
+    fn test_department_limits_candidates() {
+        // The department is not read as a town, nor are towns of other
+        // departments.
+        let place = resolve("Chinchiná, Caldas").unwrap();
+        assert_eq!(
+            (place.city.as_str(), place.department.as_str()),
+            ("Chinchiná", "Caldas")
+        );
+        assert_eq!(resolve("Rionegro, Santander").unwrap().dane_code, "68615");
+        assert_eq!(resolve("Rionegro").unwrap().dane_code, "05615");
+        let caldas = resolve("Caldas, Antioquia").unwrap();
+        assert_eq!(
+            (caldas.city.as_str(), caldas.department.as_str()),
+            ("Caldas", "Antioquia")
+        );
+        assert_eq!(resolve("Caldas").unwrap().dane_code, "05129");
+        assert_eq!(resolve("Suba - Bogotá D.C.").unwrap().city, "Bogotá");
+    }
+
+    #[test]
+    fn test_shared_names_prefer_the_larger_town() {
+        assert_eq!(resolve("Armenia").unwrap().dane_code, "63001");
+        assert_eq!(resolve("Belén, Medellín").unwrap().city, "Medellín");
+        assert_eq!(resolve("San Antonio Cali").unwrap().city, "Cali");
+        assert_eq!(resolve("Belén, Nariño").unwrap().dane_code, "52083");
+    }
+
+    #[test]
+    fn test_misspellings() {
+        let place = resolve("Medelin").unwrap();
+        assert_eq!(
+            (place.dane_code.as_str(), place.matched),
+            ("05001", Match::Fuzzy)
+        );
+        assert_eq!(resolve("Bucaramnga").unwrap().city, "Bucaramanga");
+        assert_eq!(resolve("Zipaquira, Cundinamrca").unwrap().city, "Zipaquirá");
+        assert_eq!(resolve("Bogta, Bogotá D.C.").unwrap().matched, Match::Fuzzy);
+        // Small towns are matched exactly, not taken for typos.
+        assert_eq!(resolve("Chita, Boyacá").unwrap().dane_code, "15183");
+        assert_eq!(resolve("Tota").unwrap().matched, Match::Name);
+        // Short words are never corrected.
+        assert_eq!(resolve("Tote"), None);
+    }
+
+    #[test]
+    fn test_every_municipality_is_listed() {
+        let gazetteer = &index().gazetteer;
+        assert_eq!(gazetteer.municipalities.len(), 1121);
+        assert!(gazetteer.departments.iter().all(|d| d.complete));
+        let mut codes: Vec<&str> = gazetteer
+            .municipalities
+            .iter()
+            .map(|m| m.dane.as_str())
+            .collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), gazetteer.municipalities.len());
+    }
+}
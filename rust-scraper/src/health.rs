@@ -0,0 +1,346 @@
+//! Extraction health report, so layout drift on the site shows up in the run
+//! that hit it rather than days later as empty result sets.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::listing::Listing;
+use crate::property::PropertyListing;
+
+/// Vehicle fields whose fill rate is tracked.
+pub const TRACKED_FIELDS: &[&str] = &["price", "year", "mileage", "city", "url"];
+
+/// Property fields whose fill rate is tracked.
+pub const PROPERTY_FIELDS: &[&str] = &["price", "area_m2", "city", "url"];
+
+/// A kind of listing the health report can compute fill rates for.
+pub trait Tracked {
+    /// The fields reported, and the only ones thresholds are checked for.
+    const FIELDS: &'static [&'static str];
+
+    /// Whether `field`, one of `FIELDS`, is set.
+    fn has(&self, field: &str) -> bool;
+}
+
+impl Tracked for Listing {
+    const FIELDS: &'static [&'static str] = TRACKED_FIELDS;
+
+    fn has(&self, field: &str) -> bool {
+        match field {
+            "price" => self.price.amount.is_some(),
+            "year" => self.year.is_some(),
+            "mileage" => self.mileage.is_some(),
+            "city" => self.city.is_some(),
+            "url" => !self.url.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+impl Tracked for PropertyListing {
+    const FIELDS: &'static [&'static str] = PROPERTY_FIELDS;
+
+    fn has(&self, field: &str) -> bool {
+        match field {
+            "price" => self.price.amount.is_some(),
+            "area_m2" => self.area_m2.is_some(),
+            "city" => self.city.is_some(),
+            "url" => !self.url.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+/// Marker recorded instead of a CSS selector for pages read from JSON.
+pub const NEXT_DATA_MATCH: &str = "__NEXT_DATA__";
+
+/// Marker recorded for pages read from MercadoLibre's preloaded state.
+pub const PRELOADED_STATE_MATCH: &str = "__NORDIC_RENDERING_CTX__";
+
+/// Marker recorded for pages fetched from a JSON API.
+pub const API_MATCH: &str = "search API";
+
+/// Marker recorded for pages whose listings came from JSON-LD alone.
+pub const JSON_LD_MATCH: &str = "application/ld+json";
+
+/// Marker recorded for pages that only say the search found nothing.
+pub const NO_RESULTS_MATCH: &str = "no-results message";
+
+/// Fill-rate floors below which a run is reported unhealthy.
+#[derive(Debug, Default, Clone)]
+pub struct Thresholds {
+    /// Floors for every source, from `--min-fill-rate`.
+    pub all: BTreeMap<String, f64>,
+    /// Floors for one source, keyed by its name; the extraction profile's
+    /// apply to VendeTuNave alone.  `all` takes precedence.
+    pub by_source: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+impl Thresholds {
+    fn for_source(&self, source: &str) -> BTreeMap<String, f64> {
+        let mut floors = self.by_source.get(source).cloned().unwrap_or_default();
+        floors.extend(self.all.clone());
+        floors
+    }
+}
+
+/// Fill rates of the listings one source produced.
+#[derive(Serialize, Debug, Default)]
+pub struct SourceHealth {
+    pub listings_parsed: usize,
+    /// Share of the source's listings with each tracked field set.
+    pub fill_rates: BTreeMap<String, f64>,
+    #[serde(skip)]
+    filled: BTreeMap<&'static str, usize>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct HealthReport {
+    /// How many pages each card selector (or structured-data marker) was used for.
+    pub matched_selectors: BTreeMap<String, usize>,
+    /// Pages on which neither the JSON payload nor any card selector matched.
+    pub pages_unrecognized: usize,
+    /// Sources whose first results page held no listings and was not
+    /// recognized either.
+    pub empty_sources: Vec<String>,
+    /// Cards skipped because the title rule found nothing.
+    pub skipped_empty_title: usize,
+    /// Listings parsed, before the filters; the fill rates are over these.
+    pub listings_parsed: usize,
+    /// Share of parsed listings with each tracked field set, 0.0–1.0.
+    pub fill_rates: BTreeMap<String, f64>,
+    /// The same per source; thresholds are checked against these.
+    pub sources: BTreeMap<String, SourceHealth>,
+    /// Human-readable violations, e.g. "VendeTuNave price 0.42 < 0.80".
+    pub violations: Vec<String>,
+    pub healthy: bool,
+    /// Parsed listings with each tracked field set.
+    #[serde(skip)]
+    filled: BTreeMap<&'static str, usize>,
+}
+
+impl HealthReport {
+    pub fn record_page(&mut self, matched: Option<&str>, skipped_empty_title: usize) {
+        match matched {
+            Some(selector) => {
+                *self
+                    .matched_selectors
+                    .entry(selector.to_string())
+                    .or_default() += 1
+            }
+            None => self.pages_unrecognized += 1,
+        }
+        self.skipped_empty_title += skipped_empty_title;
+    }
+
+    /// Record that `source` answered its first results page with no
+    /// listings on a page it did not recognize.  A recognized empty page is
+    /// a search that found nothing, and later empty pages only mean the
+    /// results ran out.
+    pub fn record_empty_result(&mut self, source: &str) {
+        self.empty_sources.push(source.to_string());
+    }
+
+    /// Count a listing parsed from `source` towards the fill rates, whether
+    /// or not it passes the filters, so the rates measure extraction alone.
+    pub fn record_listing<T: Tracked>(&mut self, source: &str, listing: &T) {
+        let by_source = self.sources.entry(source.to_string()).or_default();
+        self.listings_parsed += 1;
+        by_source.listings_parsed += 1;
+        for field in T::FIELDS {
+            let has = usize::from(listing.has(field));
+            *self.filled.entry(field).or_default() += has;
+            *by_source.filled.entry(field).or_default() += has;
+        }
+    }
+
+    /// Compute fill rates over the recorded listings and compare each
+    /// source's with its `thresholds`.  Unrecognized pages and sources that
+    /// returned nothing unrecognized are violations too: both are what a
+    /// layout change looks like.  Thresholds are only checked for fields the
+    /// source's kind of listing tracks, so `--min-fill-rate area_m2=…` does
+    /// not fail a vehicle search.
+    pub fn finish(&mut self, thresholds: &Thresholds) {
+        self.fill_rates = rates(&self.filled, self.listings_parsed);
+        for health in self.sources.values_mut() {
+            health.fill_rates = rates(&health.filled, health.listings_parsed);
+        }
+
+        self.violations.clear();
+        if self.pages_unrecognized > 0 {
+            self.violations
+                .push(format!("{} pages unrecognized", self.pages_unrecognized));
+        }
+        for source in &self.empty_sources {
+            self.violations.push(format!("no listings from {source}"));
+        }
+        for (source, health) in &self.sources {
+            for (field, min) in thresholds.for_source(source) {
+                let Some(actual) = health.fill_rates.get(&field).copied() else {
+                    continue;
+                };
+                if actual < min {
+                    self.violations
+                        .push(format!("{source} {field} {actual:.2} < {min:.2}"));
+                }
+            }
+        }
+        self.healthy = self.violations.is_empty();
+    }
+}
+
+fn rates(filled: &BTreeMap<&'static str, usize>, total: usize) -> BTreeMap<String, f64> {
+    filled
+        .iter()
+        .map(|(field, filled)| (field.to_string(), *filled as f64 / total as f64))
+        .collect()
+}
+
+/// Parse a `FIELD=RATE` CLI threshold such as `price=0.8`.
+pub fn parse_threshold(arg: &str) -> Result<(String, f64), String> {
+    let (field, rate) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected FIELD=RATE, got {arg:?}"))?;
+    let field = field.trim();
+    let mut known: Vec<&str> = TRACKED_FIELDS.to_vec();
+    known.extend(
+        PROPERTY_FIELDS
+            .iter()
+            .filter(|f| !TRACKED_FIELDS.contains(f)),
+    );
+    if !known.contains(&field) {
+        return Err(format!(
+            "unknown field {field:?}; expected one of {}",
+            known.join(", ")
+        ));
+    }
+    let rate: f64 = rate
+        .trim()
+        .parse()
+        .map_err(|_| format!("rate {rate:?} is not a number"))?;
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(format!("rate {rate} must be between 0 and 1"));
+    }
+    Ok((field.to_string(), rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::listing::Extraction;
+    use crate::price::Price;
+
+    fn listing(price: Option<u64>, url: &str) -> Listing {
+        let mut l = Listing::new("X".into(), url.into(), "VendeTuNave", Extraction::HtmlCard);
+        l.price = price.map_or_else(Price::missing, Price::from_cop);
+        l
+    }
+
+    #[test]
+    fn test_fill_rates_and_violations() {
+        let mut report = HealthReport::default();
+        report.record_page(Some("article"), 2);
+        report.record_listing("VendeTuNave", &listing(Some(1), "u"));
+        report.record_listing("VendeTuNave", &listing(None, "u"));
+        let thresholds = Thresholds {
+            all: BTreeMap::from([("price".to_string(), 0.8), ("url".to_string(), 0.5)]),
+            ..Thresholds::default()
+        };
+        report.finish(&thresholds);
+
+        assert_eq!(report.matched_selectors.get("article"), Some(&1));
+        assert_eq!(report.skipped_empty_title, 2);
+        assert_eq!(report.listings_parsed, 2);
+        assert_eq!(report.fill_rates["price"], 0.5);
+        assert_eq!(report.fill_rates["url"], 1.0);
+        assert_eq!(report.sources["VendeTuNave"].listings_parsed, 2);
+        assert_eq!(report.violations, vec!["VendeTuNave price 0.50 < 0.80"]);
+        assert!(!report.healthy);
+    }
+
+    #[test]
+    fn test_thresholds_are_per_source() {
+        let mut report = HealthReport::default();
+        report.record_listing("VendeTuNave", &listing(Some(1), "u"));
+        report.record_listing("TuCarro", &listing(None, "u"));
+        let thresholds = Thresholds {
+            all: BTreeMap::from([("url".to_string(), 1.0)]),
+            by_source: BTreeMap::from([(
+                "VendeTuNave".to_string(),
+                BTreeMap::from([("price".to_string(), 0.8)]),
+            )]),
+        };
+        report.finish(&thresholds);
+
+        assert_eq!(report.fill_rates["price"], 0.5);
+        assert_eq!(report.sources["TuCarro"].fill_rates["price"], 0.0);
+        assert!(report.healthy);
+    }
+
+    #[test]
+    fn test_unrecognized_and_empty_pages_are_violations() {
+        let mut report = HealthReport::default();
+        report.record_page(None, 0);
+        report.record_empty_result("VendeTuNave");
+        report.finish(&Thresholds::default());
+        assert_eq!(
+            report.violations,
+            vec!["1 pages unrecognized", "no listings from VendeTuNave"]
+        );
+        assert!(report.fill_rates.is_empty());
+        assert!(!report.healthy);
+
+        let mut report = HealthReport::default();
+        report.record_page(Some("article"), 0);
+        report.record_listing("VendeTuNave", &listing(Some(1), "u"));
+        report.finish(&Thresholds::default());
+        assert!(report.healthy);
+
+        // A search that found nothing, on a page that says so.
+        let mut report = HealthReport::default();
+        report.record_page(Some(NO_RESULTS_MATCH), 0);
+        report.finish(&Thresholds::default());
+        assert!(report.healthy);
+    }
+
+    #[test]
+    fn test_property_fill_rates_skip_vehicle_thresholds() {
+        let mut property = PropertyListing::new(
+            "Bodega".into(),
+            "u".into(),
+            "FincaRaiz",
+            Extraction::HtmlCard,
+        );
+        property.area_m2 = Some(450.0);
+        let mut report = HealthReport::default();
+        report.record_listing("FincaRaiz", &property);
+        let thresholds = Thresholds {
+            all: BTreeMap::from([("year".to_string(), 0.5), ("price".to_string(), 0.8)]),
+            by_source: BTreeMap::from([(
+                "VendeTuNave".to_string(),
+                BTreeMap::from([("area_m2".to_string(), 0.9)]),
+            )]),
+        };
+        report.finish(&thresholds);
+
+        assert_eq!(
+            report.fill_rates.keys().collect::<Vec<_>>(),
+            ["area_m2", "city", "price", "url"]
+        );
+        assert_eq!(report.fill_rates["area_m2"], 1.0);
+        assert_eq!(report.violations, vec!["FincaRaiz price 0.00 < 0.80"]);
+    }
+
+    #[test]
+    fn test_parse_threshold() {
+        assert_eq!(parse_threshold("price=0.8"), Ok(("price".to_string(), 0.8)));
+        assert!(parse_threshold("price").is_err());
+        assert!(parse_threshold("colour=0.5").is_err());
+        assert_eq!(
+            parse_threshold("area_m2=0.5"),
+            Ok(("area_m2".to_string(), 0.5))
+        );
+        assert!(parse_threshold("year=1.5").is_err());
+    }
+}
@@ -0,0 +1,259 @@
+//! Photo URLs from lazy-loading markup, and the `--download-images` store
+//! that saves each photo under its SHA-256 so reused photos share a file.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+
+use crate::listing::Listing;
+use crate::property::PropertyListing;
+use crate::sha256;
+
+/// Attributes lazy-loading libraries put the real URL in, most specific
+/// first; `src` is often a placeholder until the image scrolls into view.
+const SOURCE_ATTRIBUTES: &[&str] = &[
+    "data-src",
+    "data-lazy-src",
+    "data-lazy",
+    "data-original",
+    "data-full",
+    "src",
+];
+const SRCSET_ATTRIBUTES: &[&str] = &["data-srcset", "srcset"];
+
+/// URL fragments of spinners and blank placeholders.
+const PLACEHOLDER_MARKERS: &[&str] = &["placeholder", "blank.", "spinner", "loading.", "lazy."];
+
+/// A photo saved by `--download-images`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StoredImage {
+    pub url: String,
+    /// Where the file was written, `<dir>/<sha256>.<ext>`.
+    pub path: String,
+    pub sha256: String,
+    pub bytes: usize,
+}
+
+/// The best URL an `<img>` or `<source>` element offers: the widest
+/// `srcset` candidate, else the first lazy-load attribute that is not a
+/// placeholder.
+pub fn element_source(element: &ElementRef) -> Option<String> {
+    let attr = |name: &str| {
+        element
+            .value()
+            .attr(name)
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    };
+    SRCSET_ATTRIBUTES
+        .iter()
+        .find_map(|a| attr(a).and_then(largest_srcset_candidate))
+        .or_else(|| SOURCE_ATTRIBUTES.iter().find_map(|a| attr(a)))
+        .filter(|url| !is_placeholder(url))
+        .map(str::to_string)
+}
+
+/// Every photo under `root`, in document order, made absolute with
+/// `absolute` and without duplicates.
+pub fn collect(root: &ElementRef, absolute: impl Fn(&str) -> Option<String>) -> Vec<String> {
+    let Ok(sel) = Selector::parse("img, picture source") else {
+        return Vec::new();
+    };
+    let mut images = Vec::new();
+    for element in root.select(&sel) {
+        if let Some(url) = element_source(&element).and_then(|src| absolute(&src))
+            && !images.contains(&url)
+        {
+            images.push(url);
+        }
+    }
+    images
+}
+
+/// The URL with the largest `w` or `x` descriptor in a `srcset`.
+fn largest_srcset_candidate(srcset: &str) -> Option<&str> {
+    srcset
+        .split(',')
+        .filter_map(|candidate| {
+            let mut parts = candidate.split_whitespace();
+            let url = parts.next()?;
+            let size = parts
+                .next()
+                .and_then(|d| d.trim_end_matches(['w', 'x']).parse::<f64>().ok())
+                .unwrap_or(1.0);
+            Some((url, size))
+        })
+        .filter(|(url, _)| !is_placeholder(url))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(url, _)| url)
+}
+
+/// Whether `url` is a data URI, spinner or blank placeholder rather than a
+/// photo.
+pub fn is_placeholder(url: &str) -> bool {
+    let lower = url.to_lowercase();
+    lower.starts_with("data:") || PLACEHOLDER_MARKERS.iter().any(|m| lower.contains(m))
+}
+
+/// Write `data` to `dir` under its content hash, unless a file with that
+/// hash is already there.
+pub fn store(
+    dir: &Path,
+    url: &str,
+    data: &[u8],
+    content_type: Option<&str>,
+) -> io::Result<StoredImage> {
+    let sha256 = sha256::hex_digest(data);
+    let path = dir.join(format!("{sha256}.{}", extension(url, content_type)));
+    if !path.exists() {
+        fs::write(&path, data)?;
+    }
+    Ok(StoredImage {
+        url: url.to_string(),
+        path: path.display().to_string(),
+        sha256,
+        bytes: data.len(),
+    })
+}
+
+/// File extension from the response content type, else from the URL path.
+fn extension(url: &str, content_type: Option<&str>) -> String {
+    let from_type = content_type
+        .and_then(|t| t.split(';').next())
+        .and_then(|t| t.trim().strip_prefix("image/"))
+        .map(|t| match t {
+            "jpeg" | "pjpeg" => "jpg",
+            "svg+xml" => "svg",
+            other => other,
+        });
+    let from_url = || {
+        let path = url.split(['?', '#']).next()?;
+        let ext = path.rsplit_once('.')?.1;
+        (!ext.is_empty() && ext.len() <= 4 && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+            .then_some(ext)
+    };
+    from_type
+        .or_else(from_url)
+        .unwrap_or("bin")
+        .to_ascii_lowercase()
+}
+
+/// A listing with a photo gallery `--download-images` can store.
+pub trait Gallery {
+    /// The photo URLs, and where to record the stored files.
+    fn gallery(&mut self) -> (&[String], &mut Vec<StoredImage>);
+}
+
+impl Gallery for Listing {
+    fn gallery(&mut self) -> (&[String], &mut Vec<StoredImage>) {
+        (&self.images, &mut self.stored_images)
+    }
+}
+
+impl Gallery for PropertyListing {
+    fn gallery(&mut self) -> (&[String], &mut Vec<StoredImage>) {
+        (&self.images, &mut self.stored_images)
+    }
+}
+
+/// Download every photo of `listings` into `dir`, recording the stored files
+/// on each listing.  Photos shared by several listings are fetched once;
+/// photos that fail to download are reported on stderr and skipped.
+pub fn download_all<'a, T: Gallery + 'a>(
+    listings: impl IntoIterator<Item = &'a mut T>,
+    dir: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(dir)?;
+    let client = crate::http_client()?;
+    let mut cache: HashMap<String, StoredImage> = HashMap::new();
+
+    for listing in listings {
+        let (urls, stored) = listing.gallery();
+        for url in urls {
+            if !cache.contains_key(url) {
+                match download(&client, url, dir) {
+                    Ok(stored) => {
+                        cache.insert(url.clone(), stored);
+                    }
+                    Err(e) => {
+                        eprintln!("Warning: failed to download {url}: {e}");
+                        continue;
+                    }
+                }
+            }
+            stored.push(cache[url].clone());
+        }
+    }
+    Ok(())
+}
+
+fn download(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    dir: &Path,
+) -> Result<StoredImage, Box<dyn std::error::Error>> {
+    let response = client.get(url).send()?.error_for_status()?;
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let data = response.bytes()?;
+    Ok(store(dir, url, &data, content_type.as_deref())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::Html;
+
+    #[test]
+    fn test_collect_lazy_and_srcset() {
+        let document = Html::parse_document(
+            r#"<div>
+                <img src="/img/placeholder.gif" data-src="/fotos/1.jpg">
+                <img srcset="https://cdn.x/2-480.jpg 480w, https://cdn.x/2-1080.jpg 1080w">
+                <picture><source data-srcset="https://cdn.x/3.webp 1x, https://cdn.x/3@2x.webp 2x">
+                    <img src="data:image/gif;base64,R0lGOD"></picture>
+                <img src="/fotos/1.jpg">
+            </div>"#,
+        );
+        let images = collect(&document.root_element(), |src| {
+            Some(if src.starts_with('/') {
+                format!("https://www.vendetunave.co{src}")
+            } else {
+                src.to_string()
+            })
+        });
+        assert_eq!(
+            images,
+            vec![
+                "https://www.vendetunave.co/fotos/1.jpg",
+                "https://cdn.x/2-1080.jpg",
+                "https://cdn.x/3@2x.webp",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_store_names_files_by_content_hash() {
+        let dir = std::env::temp_dir().join(format!("vtn-images-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let a = store(&dir, "https://cdn.x/a.JPG?w=1", b"abc", None).unwrap();
+        let b = store(&dir, "https://cdn.x/b", b"abc", Some("image/jpeg")).unwrap();
+        assert_eq!(
+            a.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(a.path, b.path);
+        assert!(a.path.ends_with(".jpg"));
+        assert_eq!(fs::read(&a.path).unwrap(), b"abc");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
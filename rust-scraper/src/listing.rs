@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::CatalogMatch;
+use crate::gazetteer::Place;
+use crate::images::StoredImage;
+use crate::mileage::DistanceUnit;
+use crate::price::Price;
+use crate::seller::SellerType;
+use crate::terms::PriceType;
+
+/// What a search looks for, selected with `--kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ListingKind {
+    /// Cars and other vehicles, output as `Listing`s.
+    #[default]
+    Vehicles,
+    /// Real estate for rent or sale, output as `PropertyListing`s.
+    Properties,
+}
+
+/// How precise a listing's coordinates are.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GeoPrecision {
+    /// The centroid of the municipality the listing's city resolved to.
+    CityCentroid,
+}
+
+/// Which extraction path produced a listing, or a single field of it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Extraction {
+    /// Read from the `__NEXT_DATA__` JSON payload embedded by Next.js.
+    NextData,
+    /// Read from the preloaded state MercadoLibre's platform (TuCarro,
+    /// MercadoLibre) embeds in its results pages.
+    PreloadedState,
+    /// Read from a marketplace's JSON API.
+    Api,
+    /// Read from a schema.org `application/ld+json` block.
+    JsonLd,
+    /// Read from `og:` / `product:` meta tags.
+    OpenGraph,
+    /// Guessed from the rendered HTML cards.
+    HtmlCard,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Listing {
+    /// The marketplace's own identifier for the listing.
+    pub id: Option<String>,
+    pub title: String,
+    pub price: Price,
+    /// Whether the asking price is fixed, negotiable or to be agreed.
+    pub price_type: Option<PriceType>,
+    pub year: Option<u32>,
+    /// How sure the extractor is of `year`, 0.0–1.0.
+    pub year_confidence: Option<f32>,
+    /// Odometer reading in kilometres.
+    pub mileage: Option<u32>,
+    /// The unit the site gave the reading in, before conversion to km.
+    pub mileage_unit: Option<DistanceUnit>,
+    /// Canonical municipality name when `city_raw` resolves, else the
+    /// raw text.
+    pub city: Option<String>,
+    /// The location text exactly as the site showed it.
+    pub city_raw: Option<String>,
+    pub location: Option<Place>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub geo_precision: Option<GeoPrecision>,
+    pub brand: Option<String>,
+    pub model: Option<String>,
+    pub fuel: Option<String>,
+    pub transmission: Option<String>,
+    pub condition: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    /// Every photo in the listing's gallery, cover first.
+    pub images: Vec<String>,
+    /// Photos saved by `--download-images`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stored_images: Vec<StoredImage>,
+    /// Last digit of the license plate, which decides "pico y placa".
+    pub plate_last_digit: Option<u8>,
+    /// Canonical municipality the vehicle is registered (matriculado) in.
+    pub registration_city: Option<String>,
+    /// Normalized equipment tags from the title and description, e.g.
+    /// `armored` or `reverse_camera`.
+    pub tags: Vec<String>,
+    /// Paperwork the description vouches for (true) or admits is lapsed
+    /// (false), keyed by document: `soat`, `tecnomecanica`, ...
+    pub document_status: BTreeMap<String, bool>,
+    /// Whether the vehicle is armored (blindado); `None` when not stated.
+    pub armored: Option<bool>,
+    /// Armor protection level, e.g. "3" or "2+".
+    pub armor_level: Option<String>,
+    pub seller_type: Option<SellerType>,
+    pub accepts_financing: Option<bool>,
+    /// Whether the seller takes another vehicle as part payment (permuta).
+    pub accepts_trade_in: Option<bool>,
+    /// Publication time in RFC 3339 UTC, resolved against the fetch time.
+    pub published_at: Option<String>,
+    /// The publication date exactly as the site showed it.
+    pub published_raw: Option<String>,
+    /// Make, model, trim and engine recognised from the title.
+    pub catalog: Option<CatalogMatch>,
+    pub url: String,
+    pub source: String,
+    pub extraction: Extraction,
+    /// Which path supplied each populated field, keyed by field name.
+    pub provenance: BTreeMap<String, Extraction>,
+}
+
+impl Listing {
+    /// A listing with only the mandatory fields set.
+    pub fn new(title: String, url: String, source: &str, extraction: Extraction) -> Self {
+        Listing {
+            id: None,
+            title,
+            price: Price::missing(),
+            price_type: None,
+            year: None,
+            year_confidence: None,
+            mileage: None,
+            mileage_unit: None,
+            city: None,
+            city_raw: None,
+            location: None,
+            latitude: None,
+            longitude: None,
+            geo_precision: None,
+            brand: None,
+            model: None,
+            fuel: None,
+            transmission: None,
+            condition: None,
+            description: None,
+            image_url: None,
+            images: Vec::new(),
+            stored_images: Vec::new(),
+            plate_last_digit: None,
+            registration_city: None,
+            tags: Vec::new(),
+            document_status: BTreeMap::new(),
+            armored: None,
+            armor_level: None,
+            seller_type: None,
+            accepts_financing: None,
+            accepts_trade_in: None,
+            published_at: None,
+            published_raw: None,
+            catalog: None,
+            url,
+            source: source.to_string(),
+            extraction,
+            provenance: BTreeMap::new(),
+        }
+    }
+
+    /// Names of the extracted (not derived) fields that currently hold a value.
+    pub fn populated_fields(&self) -> Vec<&'static str> {
+        [
+            ("id", self.id.is_some()),
+            ("title", !self.title.is_empty()),
+            ("price", self.price.amount.is_some()),
+            ("price_type", self.price_type.is_some()),
+            ("year", self.year.is_some()),
+            ("mileage", self.mileage.is_some()),
+            ("city", self.city.is_some()),
+            ("brand", self.brand.is_some()),
+            ("model", self.model.is_some()),
+            ("fuel", self.fuel.is_some()),
+            ("transmission", self.transmission.is_some()),
+            ("condition", self.condition.is_some()),
+            ("description", self.description.is_some()),
+            ("image_url", self.image_url.is_some()),
+            ("seller_type", self.seller_type.is_some()),
+            ("accepts_financing", self.accepts_financing.is_some()),
+            ("accepts_trade_in", self.accepts_trade_in.is_some()),
+            ("published_raw", self.published_raw.is_some()),
+            ("url", !self.url.is_empty()),
+        ]
+        .into_iter()
+        .filter_map(|(name, set)| set.then_some(name))
+        .collect()
+    }
+
+    /// Attribute every populated field without a recorded provenance to `source`.
+    pub fn record_provenance(&mut self, source: Extraction) {
+        for field in self.populated_fields() {
+            self.provenance.entry(field.to_string()).or_insert(source);
+        }
+    }
+}
@@ -1,200 +1,610 @@
-use clap::Parser;
-use regex::Regex;
-use scraper::{Html, Selector};
-use serde::{Deserialize, Serialize};
-
-/// Rust scraper for vendetunave.co — carros y camionetas section.
-/// Outputs a JSON array of vehicle listings to stdout.
+use clap::{Parser, Subcommand};
+use scraper::Selector;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+mod armor;
+mod catalog;
+mod dates;
+mod detail;
+mod filters;
+mod gazetteer;
+mod health;
+mod images;
+mod listing;
+mod mileage;
+mod next_data;
+mod pico_y_placa;
+mod plate;
+mod price;
+mod profile;
+mod property;
+mod published;
+mod seller;
+mod sha256;
+mod sources;
+mod structured;
+mod tags;
+mod terms;
+mod text;
+mod toml;
+mod year;
+
+use armor::ArmoredMode;
+use detail::DetailRecord;
+use filters::Filters;
+use health::HealthReport;
+use listing::{GeoPrecision, Listing, ListingKind};
+use profile::Profile;
+use property::PropertyListing;
+use sources::Source;
+
+/// Rust scraper for Colombian vehicle marketplaces, vendetunave.co by
+/// default, and, with `--kind properties`, real-estate portals.  Outputs a
+/// JSON object with the listings, the number of result pages visited and an
+/// extraction health report to stdout.
+///
+/// Exit status: 0 on success, 2 for an invalid profile or source, 3 when a field's
+/// fill rate falls below its threshold, a page is not recognized or a source
+/// returns no listings on a page that does not say the search found nothing
+/// (the output is still written).
 #[derive(Parser)]
-#[command(name = "vendetunave-scraper", about = "Scraper for vendetunave.co vehicles")]
+#[command(
+    name = "vendetunave-scraper",
+    about = "Scraper for vendetunave.co vehicles",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
     /// Search query, e.g. "Toyota Corolla 2019"
-    #[arg(short, long)]
-    query: String,
+    #[arg(short, long, required = true)]
+    query: Option<String>,
 
-    /// Maximum number of results to return (default: 20)
+    /// Maximum number of results to return per source, following result pages as needed (default: 20)
     #[arg(short, long, default_value_t = 20)]
     max_results: usize,
+
+    /// What to search for.  Properties are searched on Fincaraíz and
+    /// Bodegas y Locales; the vehicle filters below do not apply to them.
+    #[arg(long, value_enum, default_value_t = ListingKind::Vehicles)]
+    kind: ListingKind,
+
+    /// Marketplace to search, e.g. "vendetunave", or "all".  Repeatable;
+    /// defaults to vendetunave, or every source with `--kind properties`.
+    #[arg(long = "source", value_name = "SOURCE", global = true)]
+    sources: Vec<String>,
+
+    /// Extraction profile (TOML, or JSON for a `.json` path) overriding
+    /// VendeTuNave's embedded card selectors
+    #[arg(long, global = true)]
+    profile: Option<PathBuf>,
+
+    /// Root of the MercadoLibre API used by the "mercadolibre-api" source
+    #[arg(long = "mercadolibre-api-url", value_name = "URL", global = true, default_value = sources::mercadolibre_api::DEFAULT_BASE_URL)]
+    mercadolibre_api_url: String,
+
+    /// OAuth access token for the "mercadolibre-api" source; the search
+    /// answers HTTP 403 to anonymous requests
+    #[arg(
+        long = "mercadolibre-api-token",
+        value_name = "TOKEN",
+        global = true,
+        env = "MERCADOLIBRE_API_TOKEN",
+        hide_env_values = true
+    )]
+    mercadolibre_api_token: Option<String>,
+
+    /// Minimum fill rate for a field, e.g. "price=0.9", checked for every
+    /// source; overrides the profile's value, which applies to VendeTuNave
+    /// alone.  Repeatable.
+    #[arg(long = "min-fill-rate", value_name = "FIELD=RATE", value_parser = health::parse_threshold)]
+    min_fill_rates: Vec<(String, f64)>,
+
+    /// Exclude cars whose plate is restricted by pico y placa in CITY on any
+    /// of the given days, e.g. "medellin:lunes,viernes" or
+    /// "bogota:2026-10-15".  Repeatable.
+    #[arg(long = "pico-y-placa", value_name = "CITY:DAYS", value_parser = pico_y_placa::parse_filter)]
+    pico_y_placa: Vec<pico_y_placa::PicoYPlacaFilter>,
+
+    /// Keep only dealer or only private-seller listings; listings whose
+    /// seller type could not be detected are dropped.
+    #[arg(long = "seller-type", value_enum)]
+    seller_type: Option<seller::SellerType>,
+
+    /// Drop listings published more than N days ago.  Listings that show
+    /// no publication date are kept.
+    #[arg(long = "max-age-days", value_name = "N")]
+    max_age_days: Option<u32>,
+
+    /// Keep only listings tagged TAG, e.g. "armored", "sunroof" or "soat"
+    /// (a document flagged as in order).  Repeatable; all must match.
+    #[arg(long = "require-tag", value_name = "TAG", value_parser = tags::parse_tag)]
+    require_tags: Vec<String>,
+
+    /// Keep only listings whose seller offers financing.
+    #[arg(long = "accepts-financing")]
+    accepts_financing: bool,
+
+    /// Keep only listings whose seller takes a vehicle in part payment
+    /// (permuta).
+    #[arg(long = "accepts-trade-in")]
+    accepts_trade_in: bool,
+
+    /// Keep only listings with this kind of price.  Repeatable; any may
+    /// match.
+    #[arg(long = "price-type", value_name = "TYPE", value_enum)]
+    price_types: Vec<terms::PriceType>,
+
+    /// What to do with armored (blindado) listings: drop them, keep only
+    /// them, or move them to a separate `armored_listings` array.
+    #[arg(long = "armored", value_name = "MODE", value_enum)]
+    armored: Option<ArmoredMode>,
+
+    /// Download every listing photo into DIR, named by its SHA-256, and
+    /// record the local paths and hashes in the output.
+    #[arg(long = "download-images", value_name = "DIR", global = true)]
+    download_images: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Listing {
-    title: String,
-    price: Option<String>,
-    year: Option<u32>,
-    mileage: Option<u32>,
-    city: Option<String>,
-    url: String,
-    source: String,
+#[derive(Subcommand)]
+enum Command {
+    /// Fetch individual listing pages and output enriched records.
+    Detail {
+        /// Listing URLs, or bare vehicle ids such as "4521"
+        #[arg(required = true)]
+        targets: Vec<String>,
+    },
 }
 
 fn main() {
     let args = Args::parse();
 
-    match run(&args.query, args.max_results) {
-        Ok(listings) => {
-            println!("{}", serde_json::to_string(&listings).unwrap_or_else(|_| "[]".to_string()));
+    let profile = match &args.profile {
+        Some(path) => match Profile::load(path) {
+            Ok(profile) => {
+                eprintln!(
+                    "Using extraction profile {:?} from {}",
+                    profile.name,
+                    path.display()
+                );
+                profile
+            }
+            Err(e) => {
+                eprintln!("Error: {}: {e}", path.display());
+                std::process::exit(2);
+            }
+        },
+        None => Profile::embedded(),
+    };
+
+    let thresholds = health::Thresholds {
+        all: args.min_fill_rates.iter().cloned().collect(),
+        by_source: BTreeMap::from([(
+            sources::vendetunave::NAME.to_string(),
+            profile.min_fill_rates.clone(),
+        )]),
+    };
+
+    let settings = sources::Settings {
+        profile,
+        mercadolibre_api: args.mercadolibre_api_url.clone(),
+        mercadolibre_api_token: args.mercadolibre_api_token.clone(),
+    };
+    // `detail` accepts URLs from any site unless sources are named.
+    let sources = match &args.command {
+        Some(Command::Detail { .. }) if args.sources.is_empty() => sources::registry(settings),
+        _ if args.kind == ListingKind::Properties => Vec::new(),
+        _ => sources::select(&args.sources, settings).unwrap_or_else(|e| invalid(&e)),
+    };
+
+    let filters = Filters {
+        pico_y_placa: args.pico_y_placa.clone(),
+        seller_type: args.seller_type,
+        max_age_days: args.max_age_days,
+        accepts_financing: args.accepts_financing,
+        accepts_trade_in: args.accepts_trade_in,
+        price_types: args.price_types.clone(),
+        armored: args.armored,
+        required_tags: args.require_tags.clone(),
+        now: dates::now(),
+    };
+
+    let mut healthy = true;
+    let output = match &args.command {
+        Some(Command::Detail { targets }) => {
+            run_detail(targets, &sources).and_then(|mut records| {
+                if let Some(dir) = &args.download_images {
+                    images::download_all(records.iter_mut().map(|r| &mut r.listing), dir)?;
+                }
+                Ok(serde_json::to_string(&records)?)
+            })
+        }
+        None if args.kind == ListingKind::Properties => {
+            let sources = sources::select_properties(&args.sources).unwrap_or_else(|e| invalid(&e));
+            search(&args, &sources, &filters, &thresholds, &mut healthy)
+        }
+        None => search(&args, &sources, &filters, &thresholds, &mut healthy),
+    };
+
+    match output {
+        Ok(json) => {
+            println!("{json}");
+            if !healthy {
+                std::process::exit(3);
+            }
         }
         Err(e) => {
             eprintln!("Error: {e}");
-            println!("[]");
+            if args.command.is_some() {
+                println!("[]");
+            } else {
+                println!("{}", serde_json::json!(ScrapeOutput::<Listing>::default()));
+            }
         }
     }
 }
 
-fn run(query: &str, max_results: usize) -> Result<Vec<Listing>, Box<dyn std::error::Error>> {
-    // vendetunave.co accepts the search term via the `search` query parameter on
-    // the carros y camionetas category page.
-    let encoded_query = url_encode(query);
-    let url = format!(
-        "https://www.vendetunave.co/vehiculos/carrosycamionetas?search={encoded_query}"
-    );
+/// Report an invalid profile, source or option and exit with status 2.
+fn invalid(message: &str) -> ! {
+    eprintln!("Error: {message}");
+    std::process::exit(2);
+}
 
-    let client = reqwest::blocking::Client::builder()
-        .user_agent(
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) \
-             AppleWebKit/537.36 (KHTML, like Gecko) \
-             Chrome/120.0.0.0 Safari/537.36",
-        )
-        .timeout(std::time::Duration::from_secs(30))
-        .build()?;
+/// Run the search, download photos if asked, and serialize the output.
+/// `healthy` is cleared when the health report records a violation.
+fn search<T: Record>(
+    args: &Args,
+    sources: &[Box<dyn Source<T>>],
+    filters: &Filters,
+    thresholds: &health::Thresholds,
+    healthy: &mut bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut output = run(
+        args.query.as_deref().unwrap_or_default(),
+        args.max_results,
+        sources,
+        filters,
+        thresholds,
+    )?;
+    *healthy = output.health.healthy;
+    if let Some(dir) = &args.download_images {
+        images::download_all(
+            output
+                .listings
+                .iter_mut()
+                .chain(&mut output.armored_listings),
+            dir,
+        )?;
+    }
+    Ok(serde_json::to_string(&output)?)
+}
 
-    let response = client
-        .get(&url)
-        .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
-        .header("Accept-Language", "es-CO,es;q=0.9,en;q=0.8")
-        .header("DNT", "1")
-        .send()?;
+/// Upper bound on result pages followed in one run, so a site that ignores
+/// the page parameter cannot keep us looping.
+const MAX_PAGES: usize = 50;
+
+/// Search results plus run metadata, as written to stdout.
+#[derive(Serialize, Debug)]
+struct ScrapeOutput<T = Listing> {
+    pages_visited: usize,
+    listings: Vec<T>,
+    /// Armored listings, kept apart under `--armored segment`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    armored_listings: Vec<T>,
+    health: HealthReport,
+}
 
-    if !response.status().is_success() {
-        eprintln!(
-            "Warning: vendetunave.co returned HTTP {} for query: {}",
-            response.status(),
-            query
-        );
-        return Ok(vec![]);
+impl<T> Default for ScrapeOutput<T> {
+    fn default() -> Self {
+        ScrapeOutput {
+            pages_visited: 0,
+            listings: Vec::new(),
+            armored_listings: Vec::new(),
+            health: HealthReport::default(),
+        }
     }
+}
+
+/// What the search loop needs from each kind of listing.
+trait Record: Serialize + health::Tracked + images::Gallery {
+    /// What duplicates are recognised by: the URL, else the title.
+    fn key(&self) -> String;
+
+    /// Derive the normalized fields; see `enrich`.
+    fn enrich(&mut self, fetched_at: i64);
+
+    /// Whether the listing passes `filters`.
+    fn accepted(&self, filters: &Filters) -> bool;
 
-    let body = response.text()?;
-    let listings = parse_listings(&body, max_results);
-    Ok(listings)
+    /// Whether the listing goes to `armored_listings`.
+    fn segmented(&self, filters: &Filters) -> bool;
 }
 
-/// Parse vehicle listings from the HTML page.
-fn parse_listings(html: &str, max_results: usize) -> Vec<Listing> {
-    let document = Html::parse_document(html);
-    let mut listings: Vec<Listing> = Vec::new();
-
-    // vendetunave.co renders each listing card as an <article> or a <div> with
-    // a class that contains "card" or "vehicle".  We try a broad selector and
-    // then narrow down inside each matched element.
-    let card_selectors = [
-        "article.vehiculo-card",
-        "article.vehicle-card",
-        "div.vehiculo-card",
-        "div.vehicle-card",
-        "div.card-vehicle",
-        "div[class*='listing']",
-        "article",
-    ];
-
-    let card_selector = card_selectors
-        .iter()
-        .find_map(|s| Selector::parse(s).ok().filter(|sel| document.select(sel).next().is_some()))
-        .unwrap_or_else(|| Selector::parse("article").unwrap());
-
-    for card in document.select(&card_selector).take(max_results) {
-        let title = extract_text_by_selectors(
-            &card,
-            &["h2", "h3", "[class*='title']", "[class*='titulo']", "a"],
-        );
+impl Record for Listing {
+    fn key(&self) -> String {
+        if self.url.is_empty() {
+            self.title.clone()
+        } else {
+            self.url.clone()
+        }
+    }
 
-        if title.is_empty() {
-            continue;
+    fn enrich(&mut self, fetched_at: i64) {
+        enrich(self, fetched_at);
+    }
+
+    fn accepted(&self, filters: &Filters) -> bool {
+        filters.accepts(self)
+    }
+
+    fn segmented(&self, filters: &Filters) -> bool {
+        filters.armored == Some(ArmoredMode::Segment) && self.armored == Some(true)
+    }
+}
+
+/// The vehicle filters do not apply to properties.
+impl Record for PropertyListing {
+    fn key(&self) -> String {
+        if self.url.is_empty() {
+            self.title.clone()
+        } else {
+            self.url.clone()
         }
+    }
 
-        let price_raw = extract_text_by_selectors(
-            &card,
-            &["[class*='price']", "[class*='precio']", "[data-price]"],
-        );
-        let price = if price_raw.is_empty() { None } else { Some(price_raw) };
+    fn enrich(&mut self, _fetched_at: i64) {
+        property::enrich(self);
+    }
 
-        let card_text = card.text().collect::<String>();
-        let year = extract_year(&card_text);
-        let mileage = extract_mileage(&card_text);
+    fn accepted(&self, _filters: &Filters) -> bool {
+        true
+    }
+
+    fn segmented(&self, _filters: &Filters) -> bool {
+        false
+    }
+}
 
-        let city_raw = extract_text_by_selectors(
-            &card,
-            &["[class*='city']", "[class*='ciudad']", "[class*='location']", "[class*='ubicacion']"],
+/// Search every source in turn.  A source that fails is reported on
+/// stderr and skipped; the run only fails if all of them do.
+fn run<T: Record>(
+    query: &str,
+    max_results: usize,
+    sources: &[Box<dyn Source<T>>],
+    filters: &Filters,
+    thresholds: &health::Thresholds,
+) -> Result<ScrapeOutput<T>, Box<dyn std::error::Error>> {
+    let client = http_client()?;
+    let mut output = ScrapeOutput::default();
+    let mut seen = HashSet::new();
+    let mut first_error = None;
+    let mut failed = 0;
+
+    for source in sources {
+        let scraped = scrape_source(
+            source.as_ref(),
+            &client,
+            query,
+            max_results,
+            filters,
+            &mut seen,
+            &mut output,
         );
-        let city = if city_raw.is_empty() { None } else { Some(city_raw) };
-
-        // Try to find the listing URL from any <a> element inside the card
-        let url = extract_link(&card);
-
-        listings.push(Listing {
-            title,
-            price,
-            year,
-            mileage,
-            city,
-            url,
-            source: "VendeTuNave".to_string(),
-        });
+        if let Err(e) = scraped {
+            eprintln!("Warning: {} search failed: {e}", source.name());
+            failed += 1;
+            first_error.get_or_insert(e);
+        }
+    }
+    if let Some(e) = first_error
+        && failed == sources.len()
+    {
+        return Err(e);
     }
 
-    listings
+    output.health.finish(thresholds);
+    if !output.health.healthy {
+        eprintln!(
+            "Warning: extraction unhealthy: {}",
+            output.health.violations.join(", ")
+        );
+    }
+    Ok(output)
 }
 
-/// Extract the inner text of the first element matching any of the given CSS selectors.
-fn extract_text_by_selectors(
-    element: &scraper::ElementRef,
-    selectors: &[&str],
-) -> String {
-    for sel_str in selectors {
-        if let Ok(sel) = Selector::parse(sel_str) {
-            if let Some(found) = element.select(&sel).next() {
-                let text = found.text().collect::<String>().trim().to_string();
-                if !text.is_empty() {
-                    return text;
+/// Follow one source's result pages until `max_results` listings pass the
+/// filters or the results run out.  `seen` holds the URLs already output,
+/// so a listing cross-posted on an earlier page or source is kept once.
+fn scrape_source<T: Record>(
+    source: &dyn Source<T>,
+    client: &reqwest::blocking::Client,
+    query: &str,
+    max_results: usize,
+    filters: &Filters,
+    seen: &mut HashSet<String>,
+    output: &mut ScrapeOutput<T>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut kept = 0;
+    let mut url = source.search_url(query, 1);
+
+    for page in 1..=MAX_PAGES {
+        let Some(body) = source.fetch(client, &url)? else {
+            break;
+        };
+        let fetched_at = dates::now();
+        output.pages_visited += 1;
+
+        let mut parsed = source.parse_page(&body, usize::MAX);
+        if parsed.matched.is_none() && parsed.listings.is_empty() && sources::says_no_results(&body)
+        {
+            parsed.matched = Some(health::NO_RESULTS_MATCH.to_string());
+        }
+        output
+            .health
+            .record_page(parsed.matched.as_deref(), parsed.skipped_empty_title);
+        if page == 1 && parsed.listings.is_empty() && parsed.matched.is_none() {
+            output.health.record_empty_result(source.name());
+        }
+
+        let mut unseen = 0;
+        for mut listing in parsed.listings {
+            if kept >= max_results {
+                break;
+            }
+            if seen.insert(listing.key()) {
+                unseen += 1;
+                listing.enrich(fetched_at);
+                output.health.record_listing(source.name(), &listing);
+                if !listing.accepted(filters) {
+                    continue;
+                }
+                kept += 1;
+                if listing.segmented(filters) {
+                    output.armored_listings.push(listing);
+                } else {
+                    output.listings.push(listing);
                 }
             }
         }
+
+        // An empty or fully repeated page means we ran past the last one.
+        if unseen == 0 || kept >= max_results || parsed.last_page {
+            break;
+        }
+        url = parsed
+            .next_page
+            .unwrap_or_else(|| source.search_url(query, page + 1));
     }
-    String::new()
+    Ok(())
 }
 
-/// Extract the href of the first <a> inside an element, making it absolute.
-fn extract_link(element: &scraper::ElementRef) -> String {
-    if let Ok(a_sel) = Selector::parse("a[href]") {
-        if let Some(a) = element.select(&a_sel).next() {
-            if let Some(href) = a.value().attr("href") {
-                if href.starts_with("http") {
-                    return href.to_string();
+/// Fetch each listing page and parse it into a `DetailRecord`.  URLs go to
+/// the source whose site they point into; paths and bare ids to the first
+/// of `sources`.  Pages that fail to load are reported on stderr and skipped.
+fn run_detail(
+    targets: &[String],
+    sources: &[Box<dyn Source>],
+) -> Result<Vec<DetailRecord>, Box<dyn std::error::Error>> {
+    let client = http_client()?;
+    let mut records = Vec::new();
+
+    for target in targets {
+        let source = if target.trim().starts_with("http") {
+            sources.iter().find(|s| s.handles(target.trim()))
+        } else {
+            sources.first()
+        };
+        let Some(source) = source else {
+            eprintln!("Warning: no source handles {target}");
+            continue;
+        };
+        let Some(url) = source.detail_url(target) else {
+            eprintln!(
+                "Warning: cannot build a {} URL from {target:?}",
+                source.name()
+            );
+            continue;
+        };
+        match source.fetch(&client, &url) {
+            Ok(Some(body)) => match source.parse_detail(&body, &url) {
+                Some(mut record) => {
+                    enrich(&mut record.listing, dates::now());
+                    records.push(record);
                 }
-                return format!("https://www.vendetunave.co{href}");
-            }
+                None => eprintln!("Warning: no listing found on {url}"),
+            },
+            Ok(None) => {}
+            Err(e) => eprintln!("Warning: failed to fetch {url}: {e}"),
         }
     }
-    String::new()
+
+    Ok(records)
 }
 
-/// Extract a four-digit year (1980–2030) from free text.
-fn extract_year(text: &str) -> Option<u32> {
-    let re = Regex::new(r"\b(19[89][0-9]|20[0-2][0-9]|2030)\b").ok()?;
-    re.find(text)?.as_str().parse().ok()
+/// Derive the normalized fields that do not depend on which page or
+/// extraction path produced the listing.  `fetched_at` (Unix seconds) is
+/// what relative dates such as "hace 3 días" count back from.
+fn enrich(listing: &mut Listing, fetched_at: i64) {
+    listing.catalog = catalog::identify(&listing.title, listing.brand.as_deref());
+
+    listing.city_raw = listing.city.clone();
+    listing.location = listing.city_raw.as_deref().and_then(gazetteer::resolve);
+    if let Some(place) = &listing.location {
+        listing.city = Some(place.city.clone());
+        if listing.latitude.is_none() {
+            (listing.latitude, listing.longitude) =
+                (Some(place.centroid.0), Some(place.centroid.1));
+            listing.geo_precision = Some(GeoPrecision::CityCentroid);
+        }
+    }
+
+    let text = format!(
+        "{}\n{}",
+        listing.title,
+        listing.description.as_deref().unwrap_or_default()
+    );
+    if listing.plate_last_digit.is_none() {
+        listing.plate_last_digit = plate::last_digit(&text);
+    }
+    if listing.registration_city.is_none() {
+        listing.registration_city = plate::registration_city(&text);
+    }
+    terms::from_text(&text).fill(listing);
+    (listing.tags, listing.document_status) = tags::tag(&text);
+    if listing.armored.is_none()
+        && let Some(armor) = armor::detect(&text)
+    {
+        listing.armored = Some(armor.armored);
+        listing.armor_level = armor.level;
+    }
+    // `armored` may also come from detail-page attributes.
+    listing.tags.retain(|t| t != tags::ARMORED);
+    if listing.armored == Some(true) {
+        listing.tags.insert(0, tags::ARMORED.to_string());
+    }
+
+    listing.published_at = listing
+        .published_raw
+        .as_deref()
+        .and_then(|raw| published::parse(raw, fetched_at))
+        .map(dates::format_rfc3339);
+}
+
+pub(crate) fn http_client() -> reqwest::Result<reqwest::blocking::Client> {
+    reqwest::blocking::Client::builder()
+        .user_agent(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) \
+             AppleWebKit/537.36 (KHTML, like Gecko) \
+             Chrome/120.0.0.0 Safari/537.36",
+        )
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
 }
 
-/// Extract a mileage value (number followed by "km") from free text.
-fn extract_mileage(text: &str) -> Option<u32> {
-    let re = Regex::new(r"(\d{1,3}(?:[.,]\d{3})*|\d+)\s*[Kk][Mm]").ok()?;
-    let cap = re.captures(text)?;
-    let raw = cap[1].replace(['.', ','], "");
-    raw.parse().ok()
+/// Extract the inner text of the first element matching any of the given CSS selectors.
+pub(crate) fn extract_text_by_selectors(
+    element: &scraper::ElementRef,
+    selectors: &[&str],
+) -> String {
+    for sel_str in selectors {
+        if let Ok(sel) = Selector::parse(sel_str)
+            && let Some(found) = element.select(&sel).next()
+        {
+            let text = found.text().collect::<String>().trim().to_string();
+            if !text.is_empty() {
+                return text;
+            }
+        }
+    }
+    String::new()
 }
 
 /// Percent-encode a query string for use in a URL.
-fn url_encode(s: &str) -> String {
+pub(crate) fn url_encode(s: &str) -> String {
     s.chars()
         .flat_map(|c| match c {
             'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '_' | '.' | '~' => {
@@ -224,51 +634,146 @@ fn url_encode(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::listing::Extraction;
 
     #[test]
-    fn test_extract_year() {
-        assert_eq!(extract_year("Toyota Corolla 2019 automático"), Some(2019));
-        assert_eq!(extract_year("Modelo 1985"), Some(1985));
-        assert_eq!(extract_year("Sin año"), None);
+    fn test_enrich_geocodes_resolved_city() {
+        let mut listing = Listing::new(
+            "Mazda 3".into(),
+            String::new(),
+            "VendeTuNave",
+            Extraction::HtmlCard,
+        );
+        listing.city = Some("ENVIGADO - ANTIOQUIA".into());
+        enrich(&mut listing, 0);
+        assert_eq!(listing.city.as_deref(), Some("Envigado"));
+        assert_eq!(listing.city_raw.as_deref(), Some("ENVIGADO - ANTIOQUIA"));
+        assert_eq!(listing.latitude, Some(6.1759));
+        assert_eq!(listing.longitude, Some(-75.5917));
+        assert_eq!(listing.geo_precision, Some(GeoPrecision::CityCentroid));
     }
 
     #[test]
-    fn test_extract_mileage() {
-        assert_eq!(extract_mileage("45.000 km recorridos"), Some(45000));
-        assert_eq!(extract_mileage("120000km"), Some(120000));
-        assert_eq!(extract_mileage("Sin km"), None);
+    fn test_enrich_corrects_misspelled_city() {
+        let mut listing = Listing::new(
+            "Mazda 3".into(),
+            String::new(),
+            "VendeTuNave",
+            Extraction::HtmlCard,
+        );
+        listing.city = Some("Medelin".into());
+        enrich(&mut listing, 0);
+        assert_eq!(listing.city.as_deref(), Some("Medellín"));
+        assert_eq!(listing.city_raw.as_deref(), Some("Medelin"));
+        assert_eq!(
+            listing.location.map(|p| p.matched),
+            Some(gazetteer::Match::Fuzzy)
+        );
+        assert_eq!(listing.latitude, Some(6.2442));
+        assert_eq!(listing.geo_precision, Some(GeoPrecision::CityCentroid));
     }
 
     #[test]
-    fn test_url_encode() {
-        assert_eq!(url_encode("Toyota Corolla"), "Toyota+Corolla");
-        assert_eq!(url_encode("hello world"), "hello+world");
+    fn test_enrich_resolves_publication_date() {
+        // 2026-10-15T12:00:00Z
+        let fetched_at = 1_792_065_600;
+        let mut listing = Listing::new(
+            "Mazda 3".into(),
+            String::new(),
+            "VendeTuNave",
+            Extraction::HtmlCard,
+        );
+        listing.published_raw = Some("Publicado hace 10 días".into());
+        enrich(&mut listing, fetched_at);
+        assert_eq!(
+            listing.published_at.as_deref(),
+            Some("2026-10-05T12:00:00Z")
+        );
+
+        let filters = |max_age_days| Filters {
+            max_age_days: Some(max_age_days),
+            now: fetched_at,
+            ..Default::default()
+        };
+        assert!(filters(10).accepts(&listing));
+        assert!(!filters(7).accepts(&listing));
+    }
+
+    #[test]
+    fn test_enrich_reads_plate_facts_from_description() {
+        let mut listing = Listing::new(
+            "Kia Rio".into(),
+            String::new(),
+            "VendeTuNave",
+            Extraction::NextData,
+        );
+        listing.description = Some("Placa terminada en 3, matriculado en Itagüí.".into());
+        enrich(&mut listing, 0);
+        assert_eq!(listing.plate_last_digit, Some(3));
+        assert_eq!(listing.registration_city.as_deref(), Some("Itagüí"));
+
+        let filters = Filters {
+            pico_y_placa: vec![pico_y_placa::parse_filter("medellin:viernes").unwrap()],
+            ..Default::default()
+        };
+        assert!(!filters.accepts(&listing));
+    }
+
+    #[test]
+    fn test_enrich_tags_description() {
+        let mut listing = Listing::new(
+            "Toyota Fortuner 4x4".into(),
+            String::new(),
+            "VendeTuNave",
+            Extraction::NextData,
+        );
+        listing.description = Some("Único dueño. SOAT vigente, tecnomecánica vencida.".into());
+        enrich(&mut listing, 0);
+        assert_eq!(listing.tags, vec!["four_wheel_drive", "single_owner"]);
+        assert_eq!(listing.document_status.get("tecnomecanica"), Some(&false));
+
+        let requiring = |tags: &[&str]| Filters {
+            required_tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        };
+        assert!(requiring(&["four_wheel_drive", "soat"]).accepts(&listing));
+        assert!(!requiring(&["tecnomecanica"]).accepts(&listing));
+        assert!(!requiring(&["armored"]).accepts(&listing));
     }
 
     #[test]
-    fn test_parse_listings_empty_html() {
-        let listings = parse_listings("<html><body></body></html>", 20);
-        assert!(listings.is_empty());
+    fn test_enrich_detects_armor() {
+        let mut listing = Listing::new(
+            "Toyota Prado TXL blindada nivel III".into(),
+            String::new(),
+            "VendeTuNave",
+            Extraction::NextData,
+        );
+        enrich(&mut listing, 0);
+        assert_eq!(listing.armored, Some(true));
+        assert_eq!(listing.armor_level.as_deref(), Some("3"));
+        assert_eq!(listing.tags, vec!["armored"]);
+
+        let mode = |armored| Filters {
+            armored: Some(armored),
+            ..Default::default()
+        };
+        assert!(!mode(ArmoredMode::Exclude).accepts(&listing));
+        assert!(mode(ArmoredMode::Only).accepts(&listing));
+        assert!(mode(ArmoredMode::Segment).accepts(&listing));
+
+        listing.description = Some("Fue blindada, hoy desblindada con papeles.".into());
+        listing.title = "Toyota Prado TXL".into();
+        listing.armored = None;
+        enrich(&mut listing, 0);
+        assert_eq!(listing.armored, Some(false));
+        assert!(listing.tags.is_empty());
+        assert!(mode(ArmoredMode::Exclude).accepts(&listing));
     }
 
     #[test]
-    fn test_parse_listings_with_article() {
-        let html = r#"
-            <html><body>
-                <article>
-                    <h2>Toyota Corolla 2020</h2>
-                    <span class="price">$45.000.000</span>
-                    <span class="city">Medellín</span>
-                    <a href="/vehiculos/toyota-corolla-2020">Ver más</a>
-                    <p>35.000 km recorridos</p>
-                </article>
-            </body></html>
-        "#;
-        let listings = parse_listings(html, 20);
-        assert_eq!(listings.len(), 1);
-        assert_eq!(listings[0].title, "Toyota Corolla 2020");
-        assert_eq!(listings[0].year, Some(2020));
-        assert_eq!(listings[0].mileage, Some(35000));
-        assert_eq!(listings[0].source, "VendeTuNave");
+    fn test_url_encode() {
+        assert_eq!(url_encode("Toyota Corolla"), "Toyota+Corolla");
+        assert_eq!(url_encode("hello world"), "hello+world");
     }
 }
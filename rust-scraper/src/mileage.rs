@@ -0,0 +1,172 @@
+//! Odometer readings from free text: "45.000 km", "45 mil km", "45k km",
+//! "Kilometraje: 45.000", "0 km" and miles on imported vehicles.
+
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+pub const KM_PER_MILE: f64 = 1.609_344;
+
+/// Readings above this are typos or phone numbers, not odometers.
+const MAX_KM: f64 = 2_000_000.0;
+
+/// The unit an odometer reading was given in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DistanceUnit {
+    Km,
+    Miles,
+}
+
+/// A parsed odometer reading, always normalized to kilometres.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mileage {
+    pub km: u32,
+    /// The unit the text used before conversion.
+    pub unit: DistanceUnit,
+}
+
+const NUMBER: &str = r"(\d{1,3}(?:[.,]\d{3})+|\d+(?:[.,]\d{1,2})?)";
+const MULTIPLIER: &str = r"(?:\s*(mil\b|k\b))?";
+const UNIT: &str = r"(kil[oó]metros?|kms?|millas?|miles)\b";
+
+fn labelled_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(&format!(
+            r"(?i)\b(kilometraje|recorrido|od[oó]metro|millaje)\s*[:=\-]?\s*{NUMBER}{MULTIPLIER}(?:\s*{UNIT})?"
+        ))
+        .expect("labelled mileage pattern is valid")
+    })
+}
+
+fn unit_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(&format!(r"(?i)(?:^|[^\w.,]){NUMBER}{MULTIPLIER}\s*{UNIT}"))
+            .expect("mileage pattern is valid")
+    })
+}
+
+/// Text right before or after a "N km" that marks it as a distance from
+/// the reader ("a 5 km de ti", "a menos de 10 km") or a rate ("15 km/l")
+/// rather than an odometer.
+fn distance_context_re() -> &'static (Regex, Regex) {
+    static RE: OnceLock<(Regex, Regex)> = OnceLock::new();
+    RE.get_or_init(|| {
+        (
+            Regex::new(r"(?i)\b(a|a menos de|a unos|a solo|a sólo|dista|distancia de)\s*$")
+                .expect("distance prefix pattern is valid"),
+            Regex::new(
+                r"(?i)^\s*(/|de ti|de tu|de su|de distancia|de aqu[ií]|del centro|de la ciudad)",
+            )
+            .expect("distance suffix pattern is valid"),
+        )
+    })
+}
+
+/// Extract an odometer reading from free text.
+///
+/// A value after a label such as "Kilometraje:" wins over any other figure
+/// in the text; otherwise the first number with a distance unit that is not
+/// a distance from the reader is used.
+pub fn extract(text: &str) -> Option<Mileage> {
+    if let Some(mileage) = labelled_re()
+        .captures_iter(text)
+        .find_map(|cap| reading(&cap, 2, cap[1].to_lowercase() == "millaje"))
+    {
+        return Some(mileage);
+    }
+
+    let (prefix, suffix) = distance_context_re();
+    unit_re().captures_iter(text).find_map(|cap| {
+        if prefix.is_match(&text[..cap.get(1)?.start()])
+            || suffix.is_match(&text[cap.get(0)?.end()..])
+        {
+            return None;
+        }
+        reading(&cap, 1, false)
+    })
+}
+
+/// Build a reading from the number, multiplier and unit groups starting at
+/// capture group `first`.
+fn reading(cap: &Captures, first: usize, default_miles: bool) -> Option<Mileage> {
+    let number = cap.get(first)?.as_str();
+    let multiplier = cap.get(first + 1).map(|m| m.as_str().to_lowercase());
+    let unit = match cap.get(first + 2).map(|m| m.as_str().to_lowercase()) {
+        Some(u) if u.starts_with("mi") => DistanceUnit::Miles,
+        Some(_) => DistanceUnit::Km,
+        None if default_miles => DistanceUnit::Miles,
+        None => DistanceUnit::Km,
+    };
+
+    let mut value = parse_number(number)?;
+    if multiplier.is_some() {
+        value *= 1000.0;
+    }
+    if unit == DistanceUnit::Miles {
+        value *= KM_PER_MILE;
+    }
+    (value <= MAX_KM).then(|| Mileage {
+        km: value.round() as u32,
+        unit,
+    })
+}
+
+/// "45.000" and "45,000" are thousands; "45.5" and "45,5" are decimals.
+pub fn parse_number(raw: &str) -> Option<f64> {
+    match raw.rfind(['.', ',']) {
+        Some(pos) if raw.len() - pos - 1 == 3 => raw.replace(['.', ','], "").parse().ok(),
+        Some(pos) => {
+            let int = raw[..pos].replace(['.', ','], "");
+            format!("{int}.{}", &raw[pos + 1..]).parse().ok()
+        }
+        None => raw.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn km(text: &str) -> Option<u32> {
+        extract(text).map(|m| m.km)
+    }
+
+    #[test]
+    fn test_plain_and_multiplied_km() {
+        assert_eq!(km("45.000 km recorridos"), Some(45_000));
+        assert_eq!(km("120000km"), Some(120_000));
+        assert_eq!(km("45 mil km"), Some(45_000));
+        assert_eq!(km("45k km"), Some(45_000));
+        assert_eq!(km("Recorrido 62,5 mil kilómetros"), Some(62_500));
+        assert_eq!(km("Sin km"), None);
+    }
+
+    #[test]
+    fn test_new_cars_and_labels() {
+        assert_eq!(km("Mazda 2 0 km, entrega inmediata"), Some(0));
+        assert_eq!(km("Kilometraje: 45.000"), Some(45_000));
+        assert_eq!(km("KILOMETRAJE 78000 · 2019"), Some(78_000));
+        // A labelled value wins over an earlier distance-like figure.
+        assert_eq!(km("Motor 1.6, 15 km/l. Kilometraje: 80.000"), Some(80_000));
+    }
+
+    #[test]
+    fn test_miles_are_converted() {
+        let m = extract("Importado, 30.000 millas").unwrap();
+        assert_eq!(m.km, 48_280);
+        assert_eq!(m.unit, DistanceUnit::Miles);
+        assert_eq!(extract("Millaje: 10000").unwrap().unit, DistanceUnit::Miles);
+        assert_eq!(extract("45.000 km").unwrap().unit, DistanceUnit::Km);
+    }
+
+    #[test]
+    fn test_rejects_distance_from_reader() {
+        assert_eq!(km("Toyota Hilux · a 5 km de ti"), None);
+        assert_eq!(km("A 3 km del centro. 95.000 km"), Some(95_000));
+        assert_eq!(km("Llama al 3001234567 km"), None);
+    }
+}
@@ -0,0 +1,383 @@
+//! Typed model of the `__NEXT_DATA__` payload that vendetunave.co embeds in
+//! every server-rendered page.
+//!
+//! JSON path: `props.pageProps.data.vehicles`
+
+use scraper::{Html, Selector};
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
+use crate::listing::{Extraction, Listing};
+use crate::mileage::DistanceUnit;
+use crate::price::Price;
+use crate::seller;
+use crate::terms;
+use crate::year;
+
+pub const IMAGE_BASE: &str = "https://static.vendetunave.co/images/vehiculos";
+/// Keys the gallery array has been seen under.
+const IMAGE_KEYS: &[&str] = &["images", "imagenes", "fotos", "gallery"];
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct NextData {
+    pub props: Props,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Props {
+    #[serde(rename = "pageProps")]
+    pub page_props: PageProps,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct PageProps {
+    pub data: PageData,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct PageData {
+    pub vehicles: Vec<NextVehicle>,
+}
+
+/// One vehicle as serialized by the site.  Every field is optional and the
+/// numeric ones accept either JSON numbers or numeric strings, because the
+/// site is not consistent about either.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct NextVehicle {
+    #[serde(deserialize_with = "lenient_u64")]
+    pub id: Option<u64>,
+    #[serde(deserialize_with = "lenient_string")]
+    pub title: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    pub descripcion: Option<String>,
+    #[serde(deserialize_with = "lenient_u64")]
+    pub precio: Option<u64>,
+    #[serde(deserialize_with = "lenient_string")]
+    pub marca: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    pub modelo: Option<String>,
+    #[serde(deserialize_with = "lenient_u64")]
+    pub ano: Option<u64>,
+    #[serde(deserialize_with = "lenient_u64")]
+    pub kilometraje: Option<u64>,
+    #[serde(deserialize_with = "lenient_string")]
+    pub combustible: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    pub transmision: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    pub condicion: Option<String>,
+    #[serde(rename = "labelCiudad", deserialize_with = "lenient_string")]
+    pub label_ciudad: Option<String>,
+    #[serde(rename = "labelDep", deserialize_with = "lenient_string")]
+    pub label_dep: Option<String>,
+    #[serde(rename = "nameImage", deserialize_with = "lenient_string")]
+    pub name_image: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    pub extension: Option<String>,
+    #[serde(rename = "tipoPrecioLabel", deserialize_with = "lenient_string")]
+    pub tipo_precio_label: Option<String>,
+    #[serde(deserialize_with = "lenient_bool")]
+    pub financiacion: Option<bool>,
+    #[serde(deserialize_with = "lenient_bool")]
+    pub permuta: Option<bool>,
+    #[serde(
+        rename = "tipoVendedor",
+        alias = "tipo_vendedor",
+        deserialize_with = "lenient_string"
+    )]
+    pub tipo_vendedor: Option<String>,
+    #[serde(alias = "esConcesionario", deserialize_with = "lenient_bool")]
+    pub concesionario: Option<bool>,
+    #[serde(rename = "fechaPublicacion", deserialize_with = "lenient_string")]
+    pub fecha_publicacion: Option<String>,
+    #[serde(rename = "createdAt", deserialize_with = "lenient_string")]
+    pub created_at: Option<String>,
+    /// Everything not modelled above; the gallery is read from here as its
+    /// key varies between pages.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Parse the `__NEXT_DATA__` script tag of a document into untyped JSON.
+pub fn payload(document: &Html) -> Option<Value> {
+    let sel = Selector::parse("script#__NEXT_DATA__").ok()?;
+    let script = document.select(&sel).next()?;
+    let raw = script.text().collect::<String>();
+    serde_json::from_str(&raw).ok()
+}
+
+/// Parse the `__NEXT_DATA__` script tag of a document and return its vehicles.
+///
+/// Returns `None` when the tag is missing or its JSON does not parse, so the
+/// caller can fall back to the HTML card heuristics.
+pub fn extract_vehicles(document: &Html) -> Option<Vec<NextVehicle>> {
+    let data: NextData = serde_json::from_value(payload(document)?).ok()?;
+    Some(data.props.page_props.data.vehicles)
+}
+
+/// Whether the `__NEXT_DATA__` of a document holds a vehicle list at all,
+/// even an empty one: an empty list is a search that found nothing, a
+/// missing one a page whose data moved.
+pub fn has_vehicle_list(document: &Html) -> bool {
+    payload(document).is_some_and(|data| {
+        data.pointer("/props/pageProps/data/vehicles")
+            .is_some_and(Value::is_array)
+    })
+}
+
+/// Return the single vehicle object of a `/vehiculo/<id>/<slug>` detail page.
+pub fn extract_detail_vehicle(document: &Html) -> Option<Value> {
+    let data = payload(document)?;
+    [
+        "/props/pageProps/data/vehicle",
+        "/props/pageProps/data/vehiculo",
+        "/props/pageProps/vehicle",
+        "/props/pageProps/vehiculo",
+    ]
+    .iter()
+    .find_map(|path| data.pointer(path).filter(|v| v.is_object()).cloned())
+}
+
+impl NextVehicle {
+    /// Convert into a `Listing`.  Vehicles without an id or title are dropped,
+    /// as there is no way to link back to them.
+    pub fn into_listing(self) -> Option<Listing> {
+        let id = self.id?;
+        let title = self.title.filter(|t| !t.is_empty())?;
+
+        let year = self
+            .ano
+            .and_then(|y| u32::try_from(y).ok())
+            .filter(|y| (1900..=year::max_year()).contains(y));
+        let mileage = self.kilometraje.and_then(|km| u32::try_from(km).ok());
+        let city = self
+            .label_ciudad
+            .or(self.label_dep)
+            .filter(|c| !c.is_empty())
+            .map(|c| title_case(&c));
+
+        let url = format!(
+            "https://www.vendetunave.co/vehiculo/{id}/{}",
+            slugify(&title)
+        );
+        let image_url = self.name_image.map(|name| {
+            let ext = self.extension.as_deref().unwrap_or("jpeg");
+            format!("{IMAGE_BASE}/{name}.{ext}")
+        });
+        let mut images = gallery(&self.extra);
+        if let Some(cover) = &image_url
+            && !images.contains(cover)
+        {
+            images.insert(0, cover.clone());
+        }
+
+        let mut listing = Listing::new(title, url, "VendeTuNave", Extraction::NextData);
+        listing.id = Some(id.to_string());
+        listing.price = self
+            .precio
+            .map(Price::from_cop)
+            .unwrap_or_else(Price::missing);
+        listing.year = year;
+        listing.year_confidence = year.map(|_| year::STRUCTURED_CONFIDENCE);
+        listing.mileage = mileage;
+        listing.mileage_unit = mileage.map(|_| DistanceUnit::Km);
+        listing.city = city;
+        listing.brand = self.marca;
+        listing.model = self.modelo;
+        listing.fuel = self.combustible;
+        listing.transmission = self.transmision;
+        listing.condition = self.condicion;
+        listing.description = self.descripcion;
+        listing.image_url = image_url;
+        listing.images = images;
+        listing.seller_type = seller::from_flags(self.tipo_vendedor.as_deref(), self.concesionario);
+        listing.accepts_financing = self.financiacion;
+        listing.accepts_trade_in = self.permuta;
+        listing.price_type = self
+            .tipo_precio_label
+            .as_deref()
+            .and_then(terms::price_type);
+        listing.published_raw = self.fecha_publicacion.or(self.created_at);
+        listing.record_provenance(Extraction::NextData);
+        Some(listing)
+    }
+}
+
+/// Photo URLs of a vehicle's gallery array, in order and without duplicates.
+pub fn gallery(vehicle: &Map<String, Value>) -> Vec<String> {
+    let mut images = Vec::new();
+    for url in IMAGE_KEYS
+        .iter()
+        .filter_map(|k| vehicle.get(*k)?.as_array())
+        .flatten()
+        .filter_map(image_url_from_json)
+    {
+        if !images.contains(&url) {
+            images.push(url);
+        }
+    }
+    images
+}
+
+/// Gallery entries are either plain URLs or `{nameImage, extension}` objects.
+fn image_url_from_json(entry: &Value) -> Option<String> {
+    match entry {
+        Value::String(s) if s.starts_with("http") => Some(s.clone()),
+        Value::String(s) if !s.is_empty() => Some(format!("{IMAGE_BASE}/{s}")),
+        Value::Object(o) => {
+            if let Some(url) = ["url", "src"].iter().find_map(|k| o.get(*k)?.as_str()) {
+                return Some(url.to_string());
+            }
+            let name = o.get("nameImage")?.as_str()?;
+            let ext = o.get("extension").and_then(Value::as_str).unwrap_or("jpeg");
+            Some(format!("{IMAGE_BASE}/{name}.{ext}"))
+        }
+        _ => None,
+    }
+}
+
+/// Build the URL slug the site uses for `/vehiculo/<id>/<slug>`.
+pub fn slugify(title: &str) -> String {
+    title
+        .to_lowercase()
+        .replace(' ', "-")
+        .chars()
+        .filter(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '-')
+        .collect()
+}
+
+fn title_case(s: &str) -> String {
+    s.split_whitespace()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn lenient_u64<'de, D: Deserializer<'de>>(d: D) -> Result<Option<u64>, D::Error> {
+    Ok(match Value::deserialize(d)? {
+        Value::Number(n) => n
+            .as_u64()
+            .or_else(|| n.as_f64().filter(|f| *f >= 0.0).map(|f| f as u64)),
+        Value::String(s) => s
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|f| *f >= 0.0)
+            .map(|f| f as u64),
+        _ => None,
+    })
+}
+
+fn lenient_string<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    Ok(match Value::deserialize(d)? {
+        Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+fn lenient_bool<'de, D: Deserializer<'de>>(d: D) -> Result<Option<bool>, D::Error> {
+    Ok(match Value::deserialize(d)? {
+        Value::Bool(b) => Some(b),
+        Value::Number(n) => n.as_i64().map(|n| n != 0),
+        Value::String(s) => match s.trim().to_lowercase().as_str() {
+            "1" | "true" | "si" | "sí" => Some(true),
+            "0" | "false" | "no" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(json: &str) -> String {
+        format!(
+            r#"<html><body><script id="__NEXT_DATA__" type="application/json">{json}</script></body></html>"#
+        )
+    }
+
+    #[test]
+    fn test_extract_vehicles() {
+        let html = page(
+            r#"{"props":{"pageProps":{"data":{"vehicles":[
+                {"id":123,"title":"Mazda 3 Touring","precio":53000000.0,"ano":"2019",
+                 "kilometraje":42000,"labelCiudad":"MEDELLIN","financiacion":1,
+                 "permuta":"0","tipoPrecioLabel":"Negociable",
+                 "marca":"Mazda","modelo":"3","combustible":"Gasolina",
+                 "transmision":"Automática","condicion":"Usado",
+                 "descripcion":" Único dueño ","nameImage":"abc123","extension":"webp"}
+            ]}}}}"#,
+        );
+        let vehicles = extract_vehicles(&Html::parse_document(&html)).unwrap();
+        assert_eq!(vehicles.len(), 1);
+        assert_eq!(vehicles[0].precio, Some(53_000_000));
+        assert_eq!(vehicles[0].ano, Some(2019));
+        assert_eq!(vehicles[0].financiacion, Some(true));
+
+        let listing = vehicles.into_iter().next().unwrap().into_listing().unwrap();
+        assert_eq!(listing.price.amount, Some(53_000_000));
+        assert_eq!(listing.city.as_deref(), Some("Medellin"));
+        assert_eq!(
+            listing.url,
+            "https://www.vendetunave.co/vehiculo/123/mazda-3-touring"
+        );
+        assert_eq!(listing.extraction, Extraction::NextData);
+        assert_eq!(listing.id.as_deref(), Some("123"));
+        assert_eq!(listing.brand.as_deref(), Some("Mazda"));
+        assert_eq!(listing.model.as_deref(), Some("3"));
+        assert_eq!(listing.fuel.as_deref(), Some("Gasolina"));
+        assert_eq!(listing.transmission.as_deref(), Some("Automática"));
+        assert_eq!(listing.condition.as_deref(), Some("Usado"));
+        assert_eq!(listing.description.as_deref(), Some("Único dueño"));
+        assert_eq!(listing.accepts_financing, Some(true));
+        assert_eq!(listing.accepts_trade_in, Some(false));
+        assert_eq!(listing.price_type, Some(terms::PriceType::Negotiable));
+        assert_eq!(
+            listing.image_url.as_deref(),
+            Some("https://static.vendetunave.co/images/vehiculos/abc123.webp")
+        );
+    }
+
+    #[test]
+    fn test_extract_vehicles_missing_or_invalid() {
+        assert!(extract_vehicles(&Html::parse_document("<html></html>")).is_none());
+        assert!(extract_vehicles(&Html::parse_document(&page("{not json"))).is_none());
+    }
+
+    #[test]
+    fn test_into_listing_requires_id_and_title() {
+        assert!(
+            NextVehicle {
+                title: Some("X".into()),
+                ..Default::default()
+            }
+            .into_listing()
+            .is_none()
+        );
+        assert!(
+            NextVehicle {
+                id: Some(1),
+                ..Default::default()
+            }
+            .into_listing()
+            .is_none()
+        );
+    }
+}
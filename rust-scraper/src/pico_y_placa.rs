@@ -0,0 +1,259 @@
+//! "Pico y placa" driving restrictions, backed by the bundled
+//! `data/pico_y_placa.json` calendar for Medellín and Bogotá.
+//!
+//! A filter such as `medellin:lunes,viernes` excludes listings whose last
+//! plate digit may not be driven in that city on those days.  Cities rotate
+//! their digits, so a city may have several entries; the newest one in
+//! force on the day applies, and weekday names are checked against the
+//! rotation in force today.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::OnceLock;
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+
+use crate::dates::Date;
+use crate::gazetteer;
+use crate::text::normalize;
+
+const WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+const DIAS: [&str; 7] = [
+    "lunes",
+    "martes",
+    "miercoles",
+    "jueves",
+    "viernes",
+    "sabado",
+    "domingo",
+];
+
+#[derive(Deserialize)]
+struct Calendar {
+    cities: Vec<CitySchedule>,
+}
+
+impl Calendar {
+    /// The rotation in force in city `dane` on `date`: the newest one that
+    /// starts on or before it.
+    fn schedule(&self, dane: &str, date: Date) -> Option<&CitySchedule> {
+        self.cities
+            .iter()
+            .filter(|c| c.dane == dane && c.valid_from <= date)
+            .max_by_key(|c| c.valid_from)
+    }
+}
+
+/// One city's rules.  Medellín restricts fixed digits per weekday; Bogotá
+/// restricts by whether the day of the month is odd or even.
+#[derive(Deserialize)]
+struct CitySchedule {
+    dane: String,
+    city: String,
+    /// First day the rotation is in force.
+    #[serde(deserialize_with = "iso_date")]
+    valid_from: Date,
+    #[serde(default)]
+    weekday_digits: BTreeMap<String, Vec<u8>>,
+    /// Weekdays on which the odd/even rule applies.
+    #[serde(default)]
+    weekdays: Vec<String>,
+    #[serde(default)]
+    odd_day_digits: Vec<u8>,
+    #[serde(default)]
+    even_day_digits: Vec<u8>,
+}
+
+impl CitySchedule {
+    fn restricted(&self, day: Day, digit: u8) -> bool {
+        let weekday = WEEKDAYS[match day {
+            Day::Weekday(w) => w,
+            Day::Date(d) => d.weekday(),
+        } as usize];
+
+        let fixed = self
+            .weekday_digits
+            .get(weekday)
+            .is_some_and(|digits| digits.contains(&digit));
+        let parity = self.weekdays.iter().any(|w| w == weekday)
+            && match day {
+                // Without a date either parity may fall on this weekday.
+                Day::Weekday(_) => {
+                    self.odd_day_digits.contains(&digit) || self.even_day_digits.contains(&digit)
+                }
+                Day::Date(d) if d.day % 2 == 1 => self.odd_day_digits.contains(&digit),
+                Day::Date(_) => self.even_day_digits.contains(&digit),
+            };
+        fixed || parity
+    }
+}
+
+fn iso_date<'de, D: Deserializer<'de>>(d: D) -> Result<Date, D::Error> {
+    let s = String::deserialize(d)?;
+    Date::parse_iso(&s).ok_or_else(|| D::Error::custom(format!("invalid date {s:?}")))
+}
+
+fn calendar() -> &'static Calendar {
+    static CALENDAR: OnceLock<Calendar> = OnceLock::new();
+    CALENDAR.get_or_init(|| {
+        serde_json::from_str(include_str!("../data/pico_y_placa.json"))
+            .expect("bundled pico y placa calendar is valid JSON")
+    })
+}
+
+/// A day to check: a weekday (0 = Monday) or a specific date.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Day {
+    Weekday(u32),
+    Date(Date),
+}
+
+impl Day {
+    fn parse(s: &str) -> Option<Self> {
+        let name = normalize(s);
+        WEEKDAYS
+            .iter()
+            .chain(&DIAS)
+            .position(|w| *w == name)
+            .map(|i| Day::Weekday((i % 7) as u32))
+            .or_else(|| Date::parse_iso(s).map(Day::Date))
+    }
+}
+
+/// Exclude cars restricted in `city` on any of `days`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PicoYPlacaFilter {
+    /// DANE code of the city whose calendar applies.
+    dane: String,
+    days: Vec<Day>,
+}
+
+impl PicoYPlacaFilter {
+    /// Whether a car whose plate ends in `digit` may not be driven on at
+    /// least one of the filter's days.  Listings without a known digit are
+    /// never excluded.
+    pub fn restricts(&self, digit: Option<u8>) -> bool {
+        self.restricts_in(calendar(), digit)
+    }
+
+    fn restricts_in(&self, calendar: &Calendar, digit: Option<u8>) -> bool {
+        let Some(digit) = digit else {
+            return false;
+        };
+        self.days.iter().any(|day| {
+            let date = match day {
+                Day::Date(d) => *d,
+                Day::Weekday(_) => Date::today(),
+            };
+            calendar
+                .schedule(&self.dane, date)
+                .is_some_and(|s| s.restricted(*day, digit))
+        })
+    }
+}
+
+/// Parse a `CITY:DAY[,DAY...]` CLI filter, e.g. `medellin:lunes,viernes` or
+/// `bogota:2026-10-15`.  Days are Spanish or English weekday names or ISO
+/// dates; Bogotá's odd/even rule is only exact for dates.
+pub fn parse_filter(arg: &str) -> Result<PicoYPlacaFilter, String> {
+    let (city, days) = arg
+        .split_once(':')
+        .ok_or_else(|| format!("expected CITY:DAY[,DAY...], got {arg:?}"))?;
+    let place = gazetteer::resolve(city).ok_or_else(|| format!("unknown city {city:?}"))?;
+    if !calendar().cities.iter().any(|c| c.dane == place.dane_code) {
+        let known: Vec<_> = calendar()
+            .cities
+            .iter()
+            .map(|c| c.city.as_str())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        return Err(format!(
+            "no pico y placa calendar for {}; available: {}",
+            place.city,
+            known.join(", ")
+        ));
+    }
+    let days = days
+        .split(',')
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| Day::parse(d).ok_or_else(|| format!("invalid day {d:?}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    if days.is_empty() {
+        return Err(format!("no days given in {arg:?}"));
+    }
+    Ok(PicoYPlacaFilter {
+        dane: place.dane_code,
+        days,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_medellin_weekdays() {
+        let filter = parse_filter("Medellín:lunes,Friday").unwrap();
+        assert!(filter.restricts(Some(6)));
+        assert!(filter.restricts(Some(3)));
+        assert!(!filter.restricts(Some(1)));
+        assert!(!filter.restricts(None));
+    }
+
+    #[test]
+    fn test_bogota_parity() {
+        // 2026-10-15 is an odd Thursday, 2026-10-17 a Saturday.
+        let filter = parse_filter("bogota:2026-10-15").unwrap();
+        assert!(filter.restricts(Some(7)));
+        assert!(!filter.restricts(Some(2)));
+        assert!(
+            !parse_filter("bogota:2026-10-17")
+                .unwrap()
+                .restricts(Some(7))
+        );
+        assert!(parse_filter("bogota:jueves").unwrap().restricts(Some(2)));
+    }
+
+    #[test]
+    fn test_newest_rotation_in_force() {
+        let calendar: Calendar = serde_json::from_str(
+            r#"{"cities": [
+                {"dane": "05001", "city": "Medellín", "valid_from": "2026-02-02",
+                 "weekday_digits": {"monday": [1, 7]}},
+                {"dane": "05001", "city": "Medellín", "valid_from": "2025-08-04",
+                 "weekday_digits": {"monday": [6, 9]}}
+            ]}"#,
+        )
+        .unwrap();
+        // 2026-01-26 and 2026-02-02 are the Mondays either side of the change.
+        let before = parse_filter("medellin:2026-01-26").unwrap();
+        assert!(before.restricts_in(&calendar, Some(6)));
+        assert!(!before.restricts_in(&calendar, Some(7)));
+        let from = parse_filter("medellin:2026-02-02").unwrap();
+        assert!(from.restricts_in(&calendar, Some(7)));
+        assert!(!from.restricts_in(&calendar, Some(6)));
+        // Nothing is in force before the oldest rotation.
+        let early = parse_filter("medellin:2025-07-28").unwrap();
+        assert!(!early.restricts_in(&calendar, Some(6)));
+    }
+
+    #[test]
+    fn test_parse_filter_errors() {
+        assert!(parse_filter("medellin").is_err());
+        assert!(parse_filter("medellin:someday").is_err());
+        assert!(
+            parse_filter("cali:lunes")
+                .unwrap_err()
+                .contains("no pico y placa calendar")
+        );
+    }
+}
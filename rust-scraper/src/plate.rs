@@ -0,0 +1,128 @@
+//! License-plate facts stated in listing text: the last plate digit, which
+//! decides "pico y placa" restrictions, and the city the car is registered
+//! (matriculado) in.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::gazetteer;
+use crate::text::normalize;
+
+fn digit_res() -> &'static [Regex] {
+    static RES: OnceLock<Vec<Regex>> = OnceLock::new();
+    RES.get_or_init(|| {
+        [
+            // "placa terminada en 7", "placas terminan en 7", "placa final 7"
+            r"(?i)\bplacas?\s*(?:terminad[oa]s?|termina[n]?|con terminaci[oó]n|final)\s*(?:en\s*)?:?\s*(\d)\b",
+            // "terminación de placa 7", "último dígito de la placa: 7"
+            r"(?i)\b(?:terminaci[oó]n|[uú]ltimo\s+d[ií]gito)\s*(?:de\s*(?:la\s*)?placa)?\s*(?:en\s*)?:?\s*(\d)\b",
+            // "placa ABC-127", "placa: abc127"
+            r"(?i)\bplacas?\s*:?\s*[a-z]{3}[\s-]?\d{2}(\d)\b",
+        ]
+        .iter()
+        .map(|re| Regex::new(re).expect("plate digit pattern is valid"))
+        .collect()
+    })
+}
+
+fn registration_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(?:matriculad[oa]|matr[ií]cula(?:\s+de)?|registrad[oa]|placas?\s+de)\s*(?:en\s*)?:?\s*([\p{L}][\p{L} .]*)",
+        )
+        .expect("registration pattern is valid")
+    })
+}
+
+/// The last digit of the plate, if the text states it.
+pub fn last_digit(text: &str) -> Option<u8> {
+    digit_res()
+        .iter()
+        .find_map(|re| re.captures(text)?[1].parse().ok())
+}
+
+/// The canonical municipality the car is registered in, if the text says
+/// "matriculado en …", "matrícula …" or "placas de …".
+pub fn registration_city(text: &str) -> Option<String> {
+    registration_re().captures_iter(text).find_map(|cap| {
+        // At most four words, so "matriculado en Bogotá y al día" resolves
+        // on "bogota y al dia" rather than the rest of the description.
+        let phrase = cap[1]
+            .split_whitespace()
+            .take(4)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let phrase = phrase.split(['.', ',']).next()?.trim();
+        gazetteer::resolve(phrase)
+            .filter(|place| place.is_exact())
+            .map(|place| place.city)
+    })
+}
+
+/// Look for the plate facts in detail-page attributes such as "Placa",
+/// "Último dígito placa" or "Ciudad de matrícula".
+pub fn from_attributes(attributes: &BTreeMap<String, String>) -> (Option<u8>, Option<String>) {
+    let mut digit = None;
+    let mut city = None;
+    for (key, value) in attributes {
+        let key = normalize(key);
+        if digit.is_none() && key.contains("placa") && !key.contains("ciudad") {
+            // "7", "terminada en 7" or a masked plate such as "***-128".
+            digit = last_digit(&format!("placa {value}")).or_else(|| {
+                let last = value.trim().chars().last()?.to_digit(10)?;
+                Some(last as u8)
+            });
+        }
+        if city.is_none() && (key.contains("matricula") || key.contains("ciudad placa")) {
+            city = gazetteer::resolve(value)
+                .filter(|place| place.is_exact())
+                .map(|place| place.city);
+        }
+    }
+    (digit, city)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_digit() {
+        assert_eq!(last_digit("Placa terminada en 7, al día"), Some(7));
+        assert_eq!(last_digit("último dígito de la placa: 0"), Some(0));
+        assert_eq!(last_digit("PLACA ABC-124 soat vigente"), Some(4));
+        assert_eq!(last_digit("Motor 2.0, 7 puestos"), None);
+    }
+
+    #[test]
+    fn test_registration_city() {
+        assert_eq!(
+            registration_city("Matriculado en Bogotá y al día").as_deref(),
+            Some("Bogotá")
+        );
+        assert_eq!(
+            registration_city("matrícula: Envigado. Único dueño").as_deref(),
+            Some("Envigado")
+        );
+        assert_eq!(
+            registration_city("Placas de Medellin").as_deref(),
+            Some("Medellín")
+        );
+        assert_eq!(registration_city("Matrícula al día"), None);
+    }
+
+    #[test]
+    fn test_from_attributes() {
+        let attributes = BTreeMap::from([
+            ("Placa".to_string(), "***-128".to_string()),
+            ("Ciudad de matrícula".to_string(), "Sabaneta".to_string()),
+        ]);
+        assert_eq!(
+            from_attributes(&attributes),
+            (Some(8), Some("Sabaneta".to_string()))
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Parsing of Colombian price strings into integer amounts.
+//!
+//! Handles the thousands-separated form ("$45.000.000", or "$95'000.000"
+//! with the apostrophe millions separator), word and letter
+//! multipliers ("45 millones", "$45M", "45.5 mill."), USD-marked prices and
+//! the usual non-prices ("Consultar", "A convenir").
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Currency {
+    #[serde(rename = "COP")]
+    Cop,
+    #[serde(rename = "USD")]
+    Usd,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceStatus {
+    /// A plain asking price.
+    Listed,
+    /// An asking price the seller marks as open to negotiation.
+    Negotiable,
+    /// No amount; the buyer has to ask ("Consultar", "A convenir").
+    OnRequest,
+    /// Nothing usable was found.
+    Missing,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Price {
+    /// Whole units of `currency` — pesos for COP, dollars for USD.
+    pub amount: Option<u64>,
+    pub currency: Currency,
+    pub status: PriceStatus,
+    /// The text the price was parsed from.
+    pub raw: String,
+}
+
+const ON_REQUEST_WORDS: &[&str] = &["consultar", "convenir", "llamar", "preguntar", "acordar"];
+const NEGOTIABLE_WORDS: &[&str] = &["negociable", "conversable", "escucho ofertas"];
+const USD_MARKERS: &[&str] = &["usd", "us$", "u$s", "dolares", "dólares", "dollars"];
+
+impl Price {
+    pub fn missing() -> Self {
+        Price {
+            amount: None,
+            currency: Currency::Cop,
+            status: PriceStatus::Missing,
+            raw: String::new(),
+        }
+    }
+
+    /// A price that is already a whole number of pesos, e.g. from JSON.
+    pub fn from_cop(amount: u64) -> Self {
+        if amount == 0 {
+            return Price::missing();
+        }
+        Price {
+            amount: Some(amount),
+            currency: Currency::Cop,
+            status: PriceStatus::Listed,
+            raw: amount.to_string(),
+        }
+    }
+
+    pub fn parse(raw: &str) -> Self {
+        let raw = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+        if raw.is_empty() {
+            return Price::missing();
+        }
+        let lower = raw.to_lowercase();
+
+        let currency = if USD_MARKERS.iter().any(|m| lower.contains(m)) {
+            Currency::Usd
+        } else {
+            Currency::Cop
+        };
+        let amount = parse_amount(&lower).filter(|a| *a > 0);
+        let status = match amount {
+            Some(_) if NEGOTIABLE_WORDS.iter().any(|w| lower.contains(w)) => {
+                PriceStatus::Negotiable
+            }
+            Some(_) => PriceStatus::Listed,
+            None if ON_REQUEST_WORDS.iter().any(|w| lower.contains(w)) => PriceStatus::OnRequest,
+            // "$0" is how several sites render "ask the seller".
+            None if lower.chars().any(|c| c.is_ascii_digit()) => PriceStatus::OnRequest,
+            None => PriceStatus::Missing,
+        };
+
+        Price {
+            amount,
+            currency,
+            status,
+            raw,
+        }
+    }
+}
+
+fn amount_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(\d[\d.,'’]*)\s*(millones|millón|millon|mills?\.?|mm|m|mil|k)?(?:\b|$|\s)")
+            .expect("amount pattern is valid")
+    })
+}
+
+/// Find the first number in `text` and apply any multiplier word after it.
+fn parse_amount(text: &str) -> Option<u64> {
+    let cap = amount_re().captures(text)?;
+    // An apostrophe only ever separates thousands (millions, in "95'000.000").
+    let number = cap[1].replace(['\'', '’'], "");
+    let number = number.trim_end_matches(['.', ',']);
+    let multiplier: u64 = match cap.get(2).map(|m| m.as_str().trim_end_matches('.')) {
+        Some("millones" | "millón" | "millon" | "mill" | "mills" | "mm" | "m") => 1_000_000,
+        Some("mil" | "k") => 1_000,
+        _ => 1,
+    };
+
+    let (int_part, frac_part) = split_decimal(number);
+    let int: u64 = int_part.parse().ok()?;
+    let mut amount = int.checked_mul(multiplier)?;
+    if !frac_part.is_empty() {
+        let frac: u64 = frac_part.parse().ok()?;
+        let scale = 10u64.checked_pow(frac_part.len() as u32)?;
+        amount = amount.checked_add((frac * multiplier + scale / 2) / scale)?;
+    }
+    Some(amount)
+}
+
+/// Split a number on its decimal separator.  A final `.` or `,` followed by
+/// exactly three digits is a thousands separator ("45.000.000"); anything
+/// else is a decimal mark ("45.5", "53000000.0", "45.000.000,00").
+fn split_decimal(number: &str) -> (String, String) {
+    match number.rfind(['.', ',']) {
+        Some(pos) if number.len() - pos - 1 != 3 => (
+            number[..pos].replace(['.', ','], ""),
+            number[pos + 1..].to_string(),
+        ),
+        _ => (number.replace(['.', ','], ""), String::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_colombian_formats() {
+        assert_eq!(Price::parse("$45.000.000").amount, Some(45_000_000));
+        assert_eq!(Price::parse("$ 45,000,000").amount, Some(45_000_000));
+        assert_eq!(Price::parse("45 millones").amount, Some(45_000_000));
+        assert_eq!(Price::parse("$45M").amount, Some(45_000_000));
+        assert_eq!(Price::parse("45.5 mill.").amount, Some(45_500_000));
+        assert_eq!(Price::parse("45,5 millones").amount, Some(45_500_000));
+        assert_eq!(Price::parse("$1.200 millones").amount, Some(1_200_000_000));
+        assert_eq!(Price::parse("850 mil").amount, Some(850_000));
+        assert_eq!(Price::parse("$95'000.000").amount, Some(95_000_000));
+        assert_eq!(
+            Price::parse("Precio: $ 120’500.000").amount,
+            Some(120_500_000)
+        );
+    }
+
+    #[test]
+    fn test_parse_decimal_suffix_is_not_a_thousands_separator() {
+        // The Python `_parse_price` turned this into 530000000.
+        assert_eq!(Price::parse("53000000.0").amount, Some(53_000_000));
+        assert_eq!(Price::parse("$45.000.000,00").amount, Some(45_000_000));
+    }
+
+    #[test]
+    fn test_parse_usd() {
+        let price = Price::parse("USD 25,000");
+        assert_eq!(price.amount, Some(25_000));
+        assert_eq!(price.currency, Currency::Usd);
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let price = Price::parse("$45.000.000 negociable");
+        assert_eq!(price.status, PriceStatus::Negotiable);
+        assert_eq!(price.raw, "$45.000.000 negociable");
+
+        let price = Price::parse("Consultar");
+        assert_eq!(price.status, PriceStatus::OnRequest);
+        assert_eq!(price.amount, None);
+
+        assert_eq!(Price::parse("$0").status, PriceStatus::OnRequest);
+        assert_eq!(Price::parse("").status, PriceStatus::Missing);
+        assert_eq!(Price::from_cop(0).status, PriceStatus::Missing);
+        assert_eq!(Price::from_cop(53_000_000).status, PriceStatus::Listed);
+    }
+}
@@ -0,0 +1,390 @@
+//! Site extraction profiles: the card, field selectors and regex
+//! post-processors used by the HTML card fallback, loaded from TOML (or
+//! JSON) so a site redesign only needs a new profile rather than a new
+//! binary.
+//!
+//! The default profile is embedded from `profiles/vendetunave.toml`; pass
+//! `--profile path` to override it.  A path ending in `.json` is read as
+//! JSON, anything else as TOML.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use serde::Deserialize;
+
+use crate::health;
+use crate::toml;
+
+/// The profile format version this binary understands.
+pub const PROFILE_VERSION: u32 = 1;
+
+const EMBEDDED_PROFILE: &str = include_str!("../profiles/vendetunave.toml");
+
+#[derive(Debug)]
+pub enum ProfileError {
+    Io(std::io::Error),
+    Toml(toml::Error),
+    Json(serde_json::Error),
+    UnsupportedVersion(u32),
+    NoCardSelectors,
+    InvalidSelector { field: String, selector: String },
+    InvalidPattern { field: String, error: regex::Error },
+    InvalidBaseUrl(String),
+    InvalidThreshold(String),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::Io(e) => write!(f, "cannot read profile: {e}"),
+            ProfileError::Toml(e) => write!(f, "profile is not valid TOML: {e}"),
+            ProfileError::Json(e) => write!(f, "profile is not valid: {e}"),
+            ProfileError::UnsupportedVersion(v) => write!(
+                f,
+                "profile version {v} is not supported (expected {PROFILE_VERSION})"
+            ),
+            ProfileError::NoCardSelectors => write!(f, "profile has no card_selectors"),
+            ProfileError::InvalidSelector { field, selector } => {
+                write!(
+                    f,
+                    "profile field `{field}`: invalid CSS selector {selector:?}"
+                )
+            }
+            ProfileError::InvalidPattern { field, error } => {
+                write!(f, "profile field `{field}`: invalid pattern: {error}")
+            }
+            ProfileError::InvalidThreshold(e) => write!(f, "profile min_fill_rates: {e}"),
+            ProfileError::InvalidBaseUrl(url) => {
+                write!(
+                    f,
+                    "profile base_url {url:?} must start with http:// or https://"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileFile {
+    version: u32,
+    name: String,
+    base_url: String,
+    card_selectors: Vec<String>,
+    fields: FieldsFile,
+    #[serde(default)]
+    min_fill_rates: BTreeMap<String, f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FieldsFile {
+    title: RuleFile,
+    price: Option<RuleFile>,
+    city: Option<RuleFile>,
+    url: Option<RuleFile>,
+    image: Option<RuleFile>,
+    year: Option<RuleFile>,
+    mileage: Option<RuleFile>,
+    seller: Option<RuleFile>,
+    seller_name: Option<RuleFile>,
+    published: Option<RuleFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    /// Tried in order; an empty list means the whole card text.
+    #[serde(default)]
+    selectors: Vec<String>,
+    /// Read this attribute instead of the element text.
+    attribute: Option<String>,
+    /// Keep only the first capture group (or the whole match) of this regex.
+    pattern: Option<String>,
+}
+
+/// How to read one field out of a card.
+pub struct FieldRule {
+    selectors: Vec<Selector>,
+    attribute: Option<String>,
+    pattern: Option<Regex>,
+}
+
+impl FieldRule {
+    fn compile_opt(field: &str, rule: Option<RuleFile>) -> Result<Option<Self>, ProfileError> {
+        rule.map(|r| FieldRule::compile(field, r)).transpose()
+    }
+
+    fn compile(field: &str, rule: RuleFile) -> Result<Self, ProfileError> {
+        let selectors = rule
+            .selectors
+            .into_iter()
+            .map(|s| parse_selector(field, s))
+            .collect::<Result<_, _>>()?;
+        let pattern = rule
+            .pattern
+            .map(|p| Regex::new(&p))
+            .transpose()
+            .map_err(|error| ProfileError::InvalidPattern {
+                field: field.to_string(),
+                error,
+            })?;
+        Ok(FieldRule {
+            selectors,
+            attribute: rule.attribute,
+            pattern,
+        })
+    }
+
+    /// The first non-empty value the rule yields for `card`.
+    pub fn extract(&self, card: &ElementRef) -> Option<String> {
+        let raw = if self.selectors.is_empty() {
+            card.text().collect::<String>()
+        } else {
+            self.selectors.iter().find_map(|sel| {
+                let found = card.select(sel).next()?;
+                let value = match &self.attribute {
+                    Some(attr) => found.value().attr(attr)?.trim().to_string(),
+                    None => found.text().collect::<String>().trim().to_string(),
+                };
+                (!value.is_empty()).then_some(value)
+            })?
+        };
+
+        match &self.pattern {
+            Some(re) => {
+                let cap = re.captures(&raw)?;
+                let m = cap.get(1).or_else(|| cap.get(0))?;
+                Some(m.as_str().trim().to_string()).filter(|s| !s.is_empty())
+            }
+            None => Some(raw),
+        }
+    }
+}
+
+/// A validated, compiled extraction profile.
+pub struct Profile {
+    pub name: String,
+    pub base_url: String,
+    card_selectors: Vec<(String, Selector)>,
+    pub title: FieldRule,
+    /// Fields left out of the profile are not extracted at all.
+    pub price: Option<FieldRule>,
+    pub city: Option<FieldRule>,
+    pub url: Option<FieldRule>,
+    pub image: Option<FieldRule>,
+    pub year: Option<FieldRule>,
+    pub mileage: Option<FieldRule>,
+    /// Seller badge, classified as dealer or private.
+    pub seller: Option<FieldRule>,
+    /// Seller name, classified when the badge says nothing.
+    pub seller_name: Option<FieldRule>,
+    /// Publication date text such as "hace 3 días".
+    pub published: Option<FieldRule>,
+    /// Fill-rate floors below which a run is reported unhealthy, checked
+    /// against the VendeTuNave listings only.
+    pub min_fill_rates: BTreeMap<String, f64>,
+}
+
+impl Profile {
+    /// The profile compiled into the binary.
+    pub fn embedded() -> Self {
+        Profile::from_toml(EMBEDDED_PROFILE).expect("embedded profile is valid")
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ProfileError> {
+        let raw = std::fs::read_to_string(path).map_err(ProfileError::Io)?;
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Profile::from_json(&raw)
+        } else {
+            Profile::from_toml(&raw)
+        }
+    }
+
+    pub fn from_toml(raw: &str) -> Result<Self, ProfileError> {
+        let value = toml::parse(raw).map_err(ProfileError::Toml)?;
+        Profile::from_file(serde_json::from_value(value).map_err(ProfileError::Json)?)
+    }
+
+    pub fn from_json(raw: &str) -> Result<Self, ProfileError> {
+        Profile::from_file(serde_json::from_str(raw).map_err(ProfileError::Json)?)
+    }
+
+    fn from_file(file: ProfileFile) -> Result<Self, ProfileError> {
+        if file.version != PROFILE_VERSION {
+            return Err(ProfileError::UnsupportedVersion(file.version));
+        }
+        if file.card_selectors.is_empty() {
+            return Err(ProfileError::NoCardSelectors);
+        }
+        if !file.base_url.starts_with("http://") && !file.base_url.starts_with("https://") {
+            return Err(ProfileError::InvalidBaseUrl(file.base_url));
+        }
+
+        let card_selectors = file
+            .card_selectors
+            .into_iter()
+            .map(|s| Ok((s.clone(), parse_selector("card_selectors", s)?)))
+            .collect::<Result<_, ProfileError>>()?;
+        let fields = file.fields;
+        let min_fill_rates = file
+            .min_fill_rates
+            .iter()
+            .map(|(field, rate)| health::parse_threshold(&format!("{field}={rate}")))
+            .collect::<Result<_, _>>()
+            .map_err(ProfileError::InvalidThreshold)?;
+
+        Ok(Profile {
+            name: file.name,
+            base_url: file.base_url.trim_end_matches('/').to_string(),
+            card_selectors,
+            title: FieldRule::compile("title", fields.title)?,
+            price: FieldRule::compile_opt("price", fields.price)?,
+            city: FieldRule::compile_opt("city", fields.city)?,
+            url: FieldRule::compile_opt("url", fields.url)?,
+            image: FieldRule::compile_opt("image", fields.image)?,
+            year: FieldRule::compile_opt("year", fields.year)?,
+            mileage: FieldRule::compile_opt("mileage", fields.mileage)?,
+            seller: FieldRule::compile_opt("seller", fields.seller)?,
+            seller_name: FieldRule::compile_opt("seller_name", fields.seller_name)?,
+            published: FieldRule::compile_opt("published", fields.published)?,
+            min_fill_rates,
+        })
+    }
+
+    /// The first card selector that matches anything in `document`, as
+    /// `(selector text, selector)`.
+    pub fn match_cards(&self, document: &Html) -> Option<(&str, &Selector)> {
+        self.card_selectors
+            .iter()
+            .find(|(_, sel)| document.select(sel).next().is_some())
+            .map(|(s, sel)| (s.as_str(), sel))
+    }
+
+    /// Make a site-relative href absolute; anything that is neither absolute
+    /// nor root-relative (`#`, `javascript:`, data URIs) is dropped.
+    pub fn absolute_url(&self, href: &str) -> Option<String> {
+        if href.starts_with("http://") || href.starts_with("https://") {
+            Some(href.to_string())
+        } else if href.starts_with('/') {
+            Some(format!("{}{href}", self.base_url))
+        } else {
+            None
+        }
+    }
+}
+
+fn parse_selector(field: &str, selector: String) -> Result<Selector, ProfileError> {
+    let parsed = Selector::parse(&selector).ok();
+    parsed.ok_or_else(|| ProfileError::InvalidSelector {
+        field: field.to_string(),
+        selector,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_profile_is_valid() {
+        let profile = Profile::embedded();
+        assert_eq!(profile.name, "vendetunave");
+        assert_eq!(
+            profile.absolute_url("/vehiculo/1/x").as_deref(),
+            Some("https://www.vendetunave.co/vehiculo/1/x")
+        );
+        assert_eq!(profile.absolute_url("#"), None);
+    }
+
+    #[test]
+    fn test_profile_validation_errors() {
+        let err = |json: &str| Profile::from_json(json).err().unwrap().to_string();
+        let base = |fields: &str| {
+            format!(
+                r#"{{"version":1,"name":"t","base_url":"https://x.co","card_selectors":["article"],"fields":{fields}}}"#
+            )
+        };
+
+        assert!(err(r#"{"version":2}"#).contains("not valid"));
+        assert!(
+            err(&base(r#"{"title":{"selectors":["h2"]}}"#)
+                .replace(r#""version":1"#, r#""version":9"#))
+            .contains("version 9 is not supported")
+        );
+        assert!(err(&base(r#"{"title":{"selectors":["[class*="]}}"#)).contains("`title`"));
+        assert!(
+            err(&base(
+                r#"{"title":{"selectors":["h2"]},"year":{"pattern":"("}}"#
+            ))
+            .contains("`year`: invalid pattern")
+        );
+        assert!(err(&base(r#"{"title":{"selectors":["h2"]},"colour":{}}"#)).contains("colour"));
+        assert!(
+            err(&base(
+                r#"{"title":{"selectors":["h2"]}},"min_fill_rates":{"colour":0.5}"#
+            ))
+            .contains("min_fill_rates: unknown field")
+        );
+    }
+
+    #[test]
+    fn test_toml_profile() {
+        let profile = Profile::from_toml(
+            r#"
+            version = 1
+            name = "t"
+            base_url = "https://x.co/"
+            card_selectors = ["div.card"]
+
+            [fields.title]
+            selectors = ["h2"]
+
+            [fields.year]
+            selectors = [".meta"]
+            pattern = 'Modelo (\d{4})'
+
+            [min_fill_rates]
+            price = 0.9
+            "#,
+        )
+        .unwrap();
+        assert_eq!(profile.base_url, "https://x.co");
+        assert_eq!(profile.min_fill_rates["price"], 0.9);
+        assert!(profile.year.is_some());
+
+        let err = |toml: &str| Profile::from_toml(toml).err().unwrap().to_string();
+        assert_eq!(
+            err("version = 1\nname = t"),
+            "profile is not valid TOML: line 2: invalid value `t`; strings need quotes"
+        );
+        assert!(err("version = 1").contains("missing field"));
+    }
+
+    #[test]
+    fn test_field_rule_attribute_and_pattern() {
+        let profile = Profile::from_json(
+            r#"{"version":1,"name":"t","base_url":"https://x.co","card_selectors":["div.card"],
+                "fields":{"title":{"selectors":["h2"]},
+                          "url":{"selectors":["a"],"attribute":"data-href"},
+                          "year":{"selectors":[".meta"],"pattern":"Modelo (\\d{4})"}}}"#,
+        )
+        .unwrap();
+        let document = Html::parse_document(
+            r#"<div class="card"><h2>Kia Rio</h2><a data-href="/v/9">x</a>
+               <span class="meta">Modelo 2017 · 2.019.000</span></div>"#,
+        );
+        let (matched, sel) = profile.match_cards(&document).unwrap();
+        assert_eq!(matched, "div.card");
+        let card = document.select(sel).next().unwrap();
+        let extract = |rule: &Option<FieldRule>| rule.as_ref().and_then(|r| r.extract(&card));
+        assert_eq!(extract(&profile.url).as_deref(), Some("/v/9"));
+        assert_eq!(extract(&profile.year).as_deref(), Some("2017"));
+        assert_eq!(extract(&profile.price), None);
+    }
+}
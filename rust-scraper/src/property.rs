@@ -0,0 +1,381 @@
+//! Real-estate listings (Fincaraíz, Bodegas y Locales): area in m², rent or
+//! sale price, property type, estrato and neighbourhood.  Kept apart from
+//! `Listing`, whose fields only make sense for vehicles.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::gazetteer::{self, Place};
+use crate::images::StoredImage;
+use crate::listing::{Extraction, GeoPrecision};
+use crate::price::Price;
+use crate::text::normalize;
+
+/// Whether the property is offered for rent or for sale.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    /// Arriendo; `price` is the monthly rent.
+    Rent,
+    /// Venta.
+    Sale,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PropertyType {
+    Apartment,
+    /// Apartaestudio.
+    Studio,
+    House,
+    Office,
+    /// Local comercial.
+    Retail,
+    /// Bodega.
+    Warehouse,
+    Lot,
+    /// Finca.
+    Farm,
+    /// Consultorio.
+    MedicalOffice,
+    Building,
+}
+
+/// Normalized words for each type, checked in order so "apartaestudio"
+/// is not read as an apartment.
+const PROPERTY_TYPES: &[(PropertyType, &[&str])] = &[
+    (
+        PropertyType::Studio,
+        &["apartaestudio", "apartaestudios", "aparta estudio"],
+    ),
+    (
+        PropertyType::Apartment,
+        &["apartamento", "apartamentos", "apto", "aptos"],
+    ),
+    (PropertyType::House, &["casa", "casas", "casalote"]),
+    (
+        PropertyType::MedicalOffice,
+        &["consultorio", "consultorios"],
+    ),
+    (PropertyType::Office, &["oficina", "oficinas"]),
+    (
+        PropertyType::Retail,
+        &["local", "locales", "local comercial"],
+    ),
+    (PropertyType::Warehouse, &["bodega", "bodegas"]),
+    (PropertyType::Lot, &["lote", "lotes", "terreno", "terrenos"]),
+    (PropertyType::Farm, &["finca", "fincas", "finca de recreo"]),
+    (PropertyType::Building, &["edificio", "edificios"]),
+];
+
+const RENT_WORDS: &[&str] = &[
+    "arriendo",
+    "arriendan",
+    "arrienda",
+    "alquiler",
+    "en arriendo",
+];
+const SALE_WORDS: &[&str] = &["venta", "vendo", "se vende", "en venta"];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PropertyListing {
+    /// The marketplace's own identifier for the listing.
+    pub id: Option<String>,
+    pub title: String,
+    pub operation: Option<Operation>,
+    /// Monthly rent when `operation` is `rent`, else the asking price.
+    pub price: Price,
+    pub property_type: Option<PropertyType>,
+    /// Built (or, for lots, total) area in square metres.
+    pub area_m2: Option<f64>,
+    /// Socio-economic stratum, 1–6.
+    pub estrato: Option<u8>,
+    /// Barrio or sector within the city.
+    pub neighbourhood: Option<String>,
+    /// Canonical municipality name when `city_raw` resolves, else the
+    /// raw text.
+    pub city: Option<String>,
+    /// The location text exactly as the site showed it.
+    pub city_raw: Option<String>,
+    pub location: Option<Place>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub geo_precision: Option<GeoPrecision>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    /// Every photo found for the listing, cover first.
+    pub images: Vec<String>,
+    /// Photos saved by `--download-images`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stored_images: Vec<StoredImage>,
+    pub url: String,
+    pub source: String,
+    pub extraction: Extraction,
+    /// Which path supplied each populated field, keyed by field name.
+    pub provenance: BTreeMap<String, Extraction>,
+}
+
+impl PropertyListing {
+    /// A listing with only the mandatory fields set.
+    pub fn new(title: String, url: String, source: &str, extraction: Extraction) -> Self {
+        PropertyListing {
+            id: None,
+            title,
+            operation: None,
+            price: Price::missing(),
+            property_type: None,
+            area_m2: None,
+            estrato: None,
+            neighbourhood: None,
+            city: None,
+            city_raw: None,
+            location: None,
+            latitude: None,
+            longitude: None,
+            geo_precision: None,
+            description: None,
+            image_url: None,
+            images: Vec::new(),
+            stored_images: Vec::new(),
+            url,
+            source: source.to_string(),
+            extraction,
+            provenance: BTreeMap::new(),
+        }
+    }
+
+    /// Names of the extracted (not derived) fields that currently hold a value.
+    pub fn populated_fields(&self) -> Vec<&'static str> {
+        [
+            ("id", self.id.is_some()),
+            ("title", !self.title.is_empty()),
+            ("operation", self.operation.is_some()),
+            ("price", self.price.amount.is_some()),
+            ("property_type", self.property_type.is_some()),
+            ("area_m2", self.area_m2.is_some()),
+            ("estrato", self.estrato.is_some()),
+            ("neighbourhood", self.neighbourhood.is_some()),
+            ("city", self.city.is_some()),
+            ("description", self.description.is_some()),
+            ("image_url", self.image_url.is_some()),
+            ("url", !self.url.is_empty()),
+        ]
+        .into_iter()
+        .filter_map(|(name, set)| set.then_some(name))
+        .collect()
+    }
+
+    /// Attribute every populated field without a recorded provenance to `source`.
+    pub fn record_provenance(&mut self, source: Extraction) {
+        for field in self.populated_fields() {
+            self.provenance.entry(field.to_string()).or_insert(source);
+        }
+    }
+
+    /// Fill unset type, operation, area and estrato from free text such as
+    /// a card or description.
+    pub fn fill_from_text(&mut self, text: &str) {
+        self.property_type = self.property_type.or_else(|| property_type(text));
+        self.operation = self.operation.or_else(|| operation(text));
+        self.area_m2 = self.area_m2.or_else(|| area_m2(text));
+        self.estrato = self.estrato.or_else(|| estrato(text));
+    }
+}
+
+fn area_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)(\d{1,3}(?:\.\d{3})+|\d+(?:,\d{1,2})?|\d+\.\d{1,2})\s*(?:m²|m2|mt2|mts2|metros\s+cuadrados)(?:[^\p{L}\d]|$)")
+            .expect("area pattern is valid")
+    })
+}
+
+fn per_month_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)\d\s*(?:/\s*mes\b|mensual)").expect("per-month pattern is valid")
+    })
+}
+
+fn estrato_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)\bestrato\s*:?\s*([1-6])\b").expect("estrato pattern is valid")
+    })
+}
+
+/// Area in m² from text such as "120 m²", "1.250 m2" or "85,5 mts2".  Bare
+/// "metros" and "mts" are skipped, as listings use them for distances ("a
+/// 200 metros del metro").
+pub fn area_m2(text: &str) -> Option<f64> {
+    let raw = &area_re().captures(text)?[1];
+    let number = if raw.contains(',') {
+        raw.replace('.', "").replace(',', ".")
+    } else if raw.matches('.').count() == 1 && raw.split('.').nth(1).is_some_and(|d| d.len() < 3) {
+        raw.to_string()
+    } else {
+        raw.replace('.', "")
+    };
+    number.parse().ok().filter(|a| *a > 0.0)
+}
+
+/// Estrato from text such as "Estrato 4".
+pub fn estrato(text: &str) -> Option<u8> {
+    estrato_re().captures(text)?[1].parse().ok()
+}
+
+/// The property type a title or description names first.
+pub fn property_type(text: &str) -> Option<PropertyType> {
+    let padded = format!(" {} ", normalize(&text.replace('.', " ")));
+    PROPERTY_TYPES
+        .iter()
+        .filter_map(|(kind, words)| {
+            words
+                .iter()
+                .filter_map(|w| padded.find(&format!(" {w} ")))
+                .min()
+                .map(|at| (at, *kind))
+        })
+        .min_by_key(|(at, _)| *at)
+        .map(|(_, kind)| kind)
+}
+
+/// Rent or sale, from words such as "arriendo" or "venta", else from a
+/// price "/ mes".  An explicit sale wins, since listings for sale quote the
+/// monthly administración fee too.
+pub fn operation(text: &str) -> Option<Operation> {
+    let padded = format!(" {} ", normalize(&text.replace('.', " ")));
+    let has = |words: &[&str]| words.iter().any(|w| padded.contains(&format!(" {w} ")));
+    if has(SALE_WORDS) {
+        Some(Operation::Sale)
+    } else if has(RENT_WORDS) || per_month_re().is_match(text) {
+        Some(Operation::Rent)
+    } else {
+        None
+    }
+}
+
+/// Every word of the operation phrases, and of the property-type phrases
+/// `text` contains, e.g. "se", "vende" and "casa" for "se vende casa
+/// Envigado"; what is left of a search query is the location.
+pub fn keyword_tokens(text: &str) -> Vec<&'static str> {
+    let padded = format!(" {} ", normalize(&text.replace('.', " ")));
+    let types = PROPERTY_TYPES
+        .iter()
+        .flat_map(|(_, words)| words.iter())
+        .filter(|w| padded.contains(&format!(" {w} ")));
+    RENT_WORDS
+        .iter()
+        .chain(SALE_WORDS)
+        .chain(types)
+        .flat_map(|w| w.split_whitespace())
+        .collect()
+}
+
+/// Resolve the city against the gazetteer and split a leading barrio off
+/// location text such as "Laureles, Medellín".
+pub fn enrich(listing: &mut PropertyListing) {
+    listing.city_raw = listing.city.clone();
+    let Some(raw) = listing.city_raw.clone() else {
+        return;
+    };
+    listing.location = gazetteer::resolve(&raw);
+    let Some(place) = &listing.location else {
+        return;
+    };
+    listing.city = Some(place.city.clone());
+    if listing.latitude.is_none() {
+        (listing.latitude, listing.longitude) = (Some(place.centroid.0), Some(place.centroid.1));
+        listing.geo_precision = Some(GeoPrecision::CityCentroid);
+    }
+    if listing.neighbourhood.is_none() {
+        let city = normalize(&place.city);
+        let department = normalize(&place.department);
+        listing.neighbourhood = raw
+            .split([',', '-'])
+            .map(str::trim)
+            .find(|part| {
+                let part = normalize(part);
+                !part.is_empty()
+                    && part != city
+                    && part != department
+                    && !city.contains(&part)
+                    && gazetteer::resolve(&part).is_none()
+            })
+            .map(str::to_string);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_fields() {
+        assert_eq!(area_m2("Bodega de 1.250 m² en Itagüí"), Some(1250.0));
+        assert_eq!(area_m2("Apartamento 85,5 mts2, 3 alcobas"), Some(85.5));
+        assert_eq!(
+            area_m2("A 300 mts del parque, apartamento de 85 m²"),
+            Some(85.0)
+        );
+        assert_eq!(area_m2("Área construida: 120 m2"), Some(120.0));
+        assert_eq!(area_m2("a 200 metros del metro"), None);
+        assert_eq!(area_m2("3 habitaciones"), None);
+
+        assert_eq!(estrato("Estrato 4, cerca al parque"), Some(4));
+        assert_eq!(estrato("estrato: 7"), None);
+
+        assert_eq!(
+            property_type("Apartaestudio amoblado en Laureles"),
+            Some(PropertyType::Studio)
+        );
+        assert_eq!(
+            property_type("Local comercial con bodega"),
+            Some(PropertyType::Retail)
+        );
+        assert_eq!(property_type("Hermosa vista"), None);
+
+        assert_eq!(operation("Casa en arriendo"), Some(Operation::Rent));
+        assert_eq!(operation("$2.500.000 / mes"), Some(Operation::Rent));
+        assert_eq!(operation("Se vende lote"), Some(Operation::Sale));
+        assert_eq!(
+            operation("Apartamento en venta, administración $450.000 / mes"),
+            Some(Operation::Sale)
+        );
+        assert_eq!(
+            operation("Casa en venta, entrega en un mes"),
+            Some(Operation::Sale)
+        );
+        assert_eq!(operation("Entrega en un mes"), None);
+    }
+
+    #[test]
+    fn test_keyword_tokens() {
+        let tokens = keyword_tokens("Local comercial Medellín");
+        assert!(
+            ["local", "comercial", "se", "vende", "arriendo"]
+                .iter()
+                .all(|w| tokens.contains(w))
+        );
+        assert!(!tokens.contains(&"medellin"));
+        assert!(!tokens.contains(&"recreo"));
+    }
+
+    #[test]
+    fn test_enrich_splits_neighbourhood() {
+        let mut listing =
+            PropertyListing::new("Apto".into(), "u".into(), "FincaRaiz", Extraction::HtmlCard);
+        listing.city = Some("Laureles, Medellín, Antioquia".into());
+        enrich(&mut listing);
+        assert_eq!(listing.city.as_deref(), Some("Medellín"));
+        assert_eq!(listing.neighbourhood.as_deref(), Some("Laureles"));
+        assert_eq!(
+            listing.city_raw.as_deref(),
+            Some("Laureles, Medellín, Antioquia")
+        );
+        assert_eq!(listing.geo_precision, Some(GeoPrecision::CityCentroid));
+    }
+}
@@ -0,0 +1,228 @@
+//! Publication timestamps from the Spanish text listings show: "hace 3 días",
+//! "Publicado hace 2 semanas", "ayer", "12 de marzo", "12/03/2024" or ISO
+//! dates from the JSON payloads.
+//!
+//! Relative phrases are resolved against the time the page was fetched.
+//! Dates without a time of day are taken as midnight in Colombia (UTC−5,
+//! which has no daylight saving).
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::dates::{self, Date, SECS_PER_DAY};
+use crate::text::normalize;
+
+/// Colombia's UTC offset in seconds.
+const COLOMBIA_OFFSET: i64 = -5 * 3600;
+
+const MONTHS: &[(&str, u32)] = &[
+    ("enero", 1),
+    ("ene", 1),
+    ("febrero", 2),
+    ("feb", 2),
+    ("marzo", 3),
+    ("mar", 3),
+    ("abril", 4),
+    ("abr", 4),
+    ("mayo", 5),
+    ("may", 5),
+    ("junio", 6),
+    ("jun", 6),
+    ("julio", 7),
+    ("jul", 7),
+    ("agosto", 8),
+    ("ago", 8),
+    ("septiembre", 9),
+    ("setiembre", 9),
+    ("sept", 9),
+    ("sep", 9),
+    ("set", 9),
+    ("octubre", 10),
+    ("oct", 10),
+    ("noviembre", 11),
+    ("nov", 11),
+    ("diciembre", 12),
+    ("dic", 12),
+];
+
+/// Relative phrases, matched on `normalize`d text: "hace 3 dias",
+/// "hace una semana", "hace 2 h".
+fn relative_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"\bhace\s+(\d+|un|una|unos|unas)\s*(segundos?|seg|minutos?|min|mins|horas?|h|hrs?|dias?|d|semanas?|sem|meses|mes|anos?)\b",
+        )
+        .expect("relative date pattern is valid")
+    })
+}
+
+/// "12 de marzo", "12 de marzo de 2024", "12 mar 2024", on normalized text.
+fn month_name_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"\b(\d{1,2})\s+(?:de\s+)?([a-z]{3,10})\.?(?:\s+(?:de\s+|del\s+)?(\d{4}))?\b")
+            .expect("month name date pattern is valid")
+    })
+}
+
+/// "12/03/2024" or "12-03-24", day first as written in Colombia.
+fn numeric_date_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"\b(\d{1,2})[/-](\d{1,2})[/-](\d{4}|\d{2})\b")
+            .expect("numeric date pattern is valid")
+    })
+}
+
+/// A "hace 3 días" / "publicado ayer" phrase inside longer card text,
+/// returned as written.
+pub fn find_phrase(text: &str) -> Option<&str> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(r"(?i)\b(?:publicado\s+)?(?:hace\s+(?:\d+|una?|un[oa]s)\s*\p{L}+|hoy|ayer|anteayer|antier)\b")
+            .expect("date phrase pattern is valid")
+    });
+    re.find(text).map(|m| m.as_str())
+}
+
+/// Resolve the publication time stated in `text` as a Unix timestamp, with
+/// relative phrases counted back from `fetched_at`.  Dates in the future are
+/// rejected.
+pub fn parse(text: &str, fetched_at: i64) -> Option<i64> {
+    let raw = text.trim();
+    if let Some(ts) = dates::parse_iso_datetime(raw, COLOMBIA_OFFSET) {
+        return (ts <= fetched_at).then_some(ts);
+    }
+
+    let text = normalize(raw);
+    let padded = format!(" {text} ");
+    let has = |phrase: &str| padded.contains(&format!(" {phrase} "));
+
+    if let Some(cap) = relative_re().captures(&text) {
+        let amount: i64 = match &cap[1] {
+            digits if digits.starts_with(|c: char| c.is_ascii_digit()) => digits.parse().ok()?,
+            _ => 1,
+        };
+        let unit: i64 = match &cap[2] {
+            u if u.starts_with("seg") => 1,
+            u if u.starts_with("min") => 60,
+            u if u.starts_with('h') => 3600,
+            u if u.starts_with('d') => SECS_PER_DAY,
+            u if u.starts_with("sem") => 7 * SECS_PER_DAY,
+            u if u.starts_with("mes") => 30 * SECS_PER_DAY,
+            _ => 365 * SECS_PER_DAY,
+        };
+        return amount
+            .checked_mul(unit)
+            .and_then(|ago| fetched_at.checked_sub(ago));
+    }
+    if [
+        "hace un momento",
+        "hace instantes",
+        "hace unos instantes",
+        "ahora",
+        "recien publicado",
+    ]
+    .iter()
+    .any(|p| has(p))
+    {
+        return Some(fetched_at);
+    }
+    if has("anteayer") || has("antier") {
+        return Some(fetched_at - 2 * SECS_PER_DAY);
+    }
+    if has("ayer") {
+        return Some(fetched_at - SECS_PER_DAY);
+    }
+    if has("hoy") {
+        return Some(fetched_at);
+    }
+
+    let today = Date::from_days((fetched_at + COLOMBIA_OFFSET).div_euclid(SECS_PER_DAY));
+    let date = month_name_re()
+        .captures_iter(&text)
+        .find_map(|cap| {
+            let month = MONTHS.iter().find(|(name, _)| *name == &cap[2])?.1;
+            absolute_date(&cap[1], month, cap.get(3).map(|y| y.as_str()), today)
+        })
+        .or_else(|| {
+            let cap = numeric_date_re().captures(raw)?;
+            absolute_date(&cap[1], cap[2].parse().ok()?, Some(&cap[3]), today)
+        })?;
+    let ts = date.days() * SECS_PER_DAY - COLOMBIA_OFFSET;
+    (ts <= fetched_at).then_some(ts)
+}
+
+/// A day and month with an optional year.  Without a year the most recent
+/// such date not after `today` is meant.
+fn absolute_date(day: &str, month: u32, year: Option<&str>, today: Date) -> Option<Date> {
+    let day = day.parse().ok()?;
+    match year {
+        Some(y) if y.len() == 2 => Date::new(2000 + y.parse::<i32>().ok()?, month, day),
+        Some(y) => Date::new(y.parse().ok()?, month, day),
+        None => Date::new(today.year, month, day)
+            .filter(|d| *d <= today)
+            .or_else(|| Date::new(today.year - 1, month, day)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2026-10-15T12:00:00Z, a Thursday.
+    const FETCHED: i64 = 1_792_065_600;
+
+    fn at(text: &str) -> Option<String> {
+        parse(text, FETCHED).map(dates::format_rfc3339)
+    }
+
+    #[test]
+    fn test_relative() {
+        assert_eq!(at("hace 3 días").as_deref(), Some("2026-10-12T12:00:00Z"));
+        assert_eq!(
+            at("Publicado hace 2 semanas").as_deref(),
+            Some("2026-10-01T12:00:00Z")
+        );
+        assert_eq!(at("Hace una hora").as_deref(), Some("2026-10-15T11:00:00Z"));
+        assert_eq!(at("hace 1 mes").as_deref(), Some("2026-09-15T12:00:00Z"));
+        assert_eq!(
+            at("Publicado ayer").as_deref(),
+            Some("2026-10-14T12:00:00Z")
+        );
+        assert_eq!(at("Hoy").as_deref(), Some("2026-10-15T12:00:00Z"));
+    }
+
+    #[test]
+    fn test_absolute() {
+        assert_eq!(at("12 de marzo").as_deref(), Some("2026-03-12T05:00:00Z"));
+        // December has not happened yet this year, so it is last December.
+        assert_eq!(
+            at("Publicado el 3 de dic.").as_deref(),
+            Some("2025-12-03T05:00:00Z")
+        );
+        assert_eq!(
+            at("12 de marzo de 2024").as_deref(),
+            Some("2024-03-12T05:00:00Z")
+        );
+        assert_eq!(at("05/10/2026").as_deref(), Some("2026-10-05T05:00:00Z"));
+        assert_eq!(at("12-03-2024").as_deref(), Some("2024-03-12T05:00:00Z"));
+        assert_eq!(
+            at("2024-05-01T10:00:00Z").as_deref(),
+            Some("2024-05-01T10:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_unparseable_and_future() {
+        assert_eq!(at("Mazda 3 2019"), None);
+        assert_eq!(at("30 de febrero"), None);
+        assert_eq!(at("2030-01-01"), None);
+        assert_eq!(at(""), None);
+        // Amounts too large to count back are not dates either.
+        assert_eq!(at("hace 999999999999 años"), None);
+        assert_eq!(at("hace 99999999999999999999 dias"), None);
+    }
+}
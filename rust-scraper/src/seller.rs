@@ -0,0 +1,105 @@
+//! Dealer vs private-seller detection from badges, seller names and the
+//! site's own flags.
+
+use serde::{Deserialize, Serialize};
+
+use crate::text::normalize;
+
+/// Who is selling the vehicle.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum SellerType {
+    /// A concesionario, compraventa or other business.
+    Dealer,
+    /// A private individual (particular).
+    Private,
+}
+
+/// Normalized phrases that mark a business seller.  Matched on word
+/// boundaries against badge text and seller names.  Words such as "autos"
+/// or "agencia" are left out: private sellers write them too ("como de
+/// agencia").
+const DEALER_MARKERS: &[&str] = &[
+    "concesionario",
+    "concesionarios",
+    "dealer",
+    "compraventa",
+    "compra venta",
+    "tienda oficial",
+    "vendedor profesional",
+    "automotriz",
+    "automotores",
+    "car center",
+    "sas",
+    "s.a.s",
+    "s.a.s.",
+    "ltda",
+    "ltda.",
+    "s.a",
+    "s.a.",
+];
+
+/// Normalized phrases that mark a private seller.  A bare "particular" is
+/// only trusted as a whole badge, since "servicio particular" describes the
+/// plate type, not the seller.
+const PRIVATE_MARKERS: &[&str] = &[
+    "vendedor particular",
+    "vende particular",
+    "persona natural",
+    "dueno directo",
+    "trato directo con el dueno",
+];
+
+/// Classify badge text or a seller name.  Dealer markers win, since a
+/// dealer card may still say "particular" in unrelated copy.
+pub fn classify(text: &str) -> Option<SellerType> {
+    let normalized = normalize(text);
+    let padded = format!(" {normalized} ");
+    let has = |markers: &[&str]| markers.iter().any(|m| padded.contains(&format!(" {m} ")));
+    if has(DEALER_MARKERS) {
+        Some(SellerType::Dealer)
+    } else if normalized == "particular" || has(PRIVATE_MARKERS) {
+        Some(SellerType::Private)
+    } else {
+        None
+    }
+}
+
+/// Interpret the site's own fields: a seller-type label such as
+/// "Concesionario" / "Particular", or a dealer flag.
+pub fn from_flags(label: Option<&str>, is_dealer: Option<bool>) -> Option<SellerType> {
+    label.and_then(classify).or(match is_dealer {
+        Some(true) => Some(SellerType::Dealer),
+        Some(false) => Some(SellerType::Private),
+        None => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("Concesionario"), Some(SellerType::Dealer));
+        assert_eq!(classify("Autos La 80 S.A.S."), Some(SellerType::Dealer));
+        assert_eq!(classify("Vendedor particular"), Some(SellerType::Private));
+        assert_eq!(classify("Dueño directo"), Some(SellerType::Private));
+        assert_eq!(classify("Particular"), Some(SellerType::Private));
+        assert_eq!(classify("Servicio particular"), None);
+        assert_eq!(classify("Carlos Pérez"), None);
+        assert_eq!(classify("Autoservicio incluido"), None);
+        assert_eq!(classify("Como de agencia, siempre en vitrina"), None);
+        assert_eq!(classify("Juan Autos"), None);
+    }
+
+    #[test]
+    fn test_from_flags() {
+        assert_eq!(
+            from_flags(Some("PARTICULAR"), Some(true)),
+            Some(SellerType::Private)
+        );
+        assert_eq!(from_flags(None, Some(true)), Some(SellerType::Dealer));
+        assert_eq!(from_flags(Some("otro"), None), None);
+    }
+}
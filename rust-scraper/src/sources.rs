@@ -0,0 +1,324 @@
+//! Marketplaces the scraper can read.  Each implements `Source`; the
+//! search loop, enrichment, filters and output in `main.rs` are shared.
+
+pub mod bodegasylocales;
+pub mod fincaraiz;
+pub mod mercadolibre;
+pub mod mercadolibre_api;
+pub mod polycard;
+pub mod property_cards;
+pub mod tucarro;
+pub mod vendetunave;
+
+use std::error::Error;
+
+use reqwest::blocking::Client;
+use scraper::{Html, Selector};
+
+use crate::detail::DetailRecord;
+use crate::listing::Listing;
+use crate::profile::Profile;
+use crate::property::PropertyListing;
+use crate::text;
+
+/// Listings parsed from one results page, plus what the health report
+/// needs to know about how they were found.  `T` is `PropertyListing` for
+/// real-estate sources.
+pub struct ParsedPage<T = Listing> {
+    pub listings: Vec<T>,
+    /// The card selector that matched, a structured-data marker such as
+    /// `__NEXT_DATA__`, or `None` when the page layout was not recognised.
+    pub matched: Option<String>,
+    pub skipped_empty_title: usize,
+    /// The next results page, when the page links to it.
+    pub next_page: Option<String>,
+    /// No further page can be requested, e.g. past an API's paging cap.
+    pub last_page: bool,
+}
+
+impl<T> ParsedPage<T> {
+    pub fn empty() -> Self {
+        ParsedPage {
+            listings: Vec::new(),
+            matched: None,
+            skipped_empty_title: 0,
+            next_page: None,
+            last_page: false,
+        }
+    }
+}
+
+/// One marketplace: how to build its URLs, fetch and parse its pages, and
+/// resolve the links found on them.  Vehicle sources produce `Listing`s,
+/// real-estate sources `Source<PropertyListing>`.
+pub trait Source<T = Listing> {
+    /// Identifier used with `--source`, e.g. `vendetunave`.
+    fn id(&self) -> &'static str;
+
+    /// Name recorded in the listings' `source`, e.g. `VendeTuNave`.
+    fn name(&self) -> &'static str;
+
+    /// Site root that root-relative links are resolved against.
+    fn base_url(&self) -> &str;
+
+    /// URL of one page of search results, counting from 1.
+    fn search_url(&self, query: &str, page: usize) -> String;
+
+    /// Parse a fetched results page into at most `max_results` listings.
+    fn parse_page(&self, body: &str, max_results: usize) -> ParsedPage<T>;
+
+    /// GET a page and return its body, or `None` (with a warning) on a
+    /// non-2xx status.
+    fn fetch(&self, client: &Client, url: &str) -> Result<Option<String>, Box<dyn Error>> {
+        fetch_html(client, url, self.name())
+    }
+
+    /// Make a link found on the site absolute; anything that is neither
+    /// absolute nor root-relative is dropped.
+    fn resolve_link(&self, href: &str) -> Option<String> {
+        resolve_link(self.base_url(), href)
+    }
+
+    /// Whether `url` points into this site, for routing `detail` targets.
+    fn handles(&self, url: &str) -> bool {
+        host(url).is_some_and(|h| host(self.base_url()) == Some(h))
+    }
+
+    /// Turn a `detail` target (URL, site path or bare id) into a URL.
+    fn detail_url(&self, target: &str) -> Option<String> {
+        self.resolve_link(target.trim())
+    }
+
+    /// Parse a vehicle listing page.  Sources without detail support return
+    /// `None`.
+    fn parse_detail(&self, _body: &str, _url: &str) -> Option<DetailRecord> {
+        None
+    }
+}
+
+/// What sources need from the command line.
+pub struct Settings {
+    /// VendeTuNave's HTML card selectors.
+    pub profile: Profile,
+    /// Root of the MercadoLibre API, overridable to test against a mock.
+    pub mercadolibre_api: String,
+    /// Access token for the MercadoLibre API.
+    pub mercadolibre_api_token: Option<String>,
+}
+
+/// Every source this binary knows, in `--source all` order.
+pub fn registry(settings: Settings) -> Vec<Box<dyn Source>> {
+    vec![
+        Box::new(vendetunave::VendeTuNave::new(settings.profile)),
+        Box::new(tucarro::TuCarro),
+        Box::new(mercadolibre::MercadoLibre),
+        Box::new(mercadolibre_api::MercadoLibreApi::new(
+            &settings.mercadolibre_api,
+            settings.mercadolibre_api_token,
+        )),
+    ]
+}
+
+/// Every real-estate source, in `--source all` order.
+pub fn property_registry() -> Vec<Box<dyn Source<PropertyListing>>> {
+    vec![
+        Box::new(fincaraiz::FincaRaiz),
+        Box::new(bodegasylocales::BodegasYLocales),
+    ]
+}
+
+/// Pick the sources named on the command line (ids, or `all`), in registry
+/// order and without duplicates.  No names means VendeTuNave alone.
+pub fn select(names: &[String], settings: Settings) -> Result<Vec<Box<dyn Source>>, String> {
+    pick(registry(settings), names, |id| id == vendetunave::ID)
+}
+
+/// `select` for `--kind properties`.  No names means every property source.
+pub fn select_properties(
+    names: &[String],
+) -> Result<Vec<Box<dyn Source<PropertyListing>>>, String> {
+    pick(property_registry(), names, |_| true)
+}
+
+/// Keep the sources of `all` that `names` asks for, or those `default`
+/// accepts when no names are given.
+fn pick<T>(
+    all: Vec<Box<dyn Source<T>>>,
+    names: &[String],
+    default: impl Fn(&str) -> bool,
+) -> Result<Vec<Box<dyn Source<T>>>, String> {
+    let known: Vec<&str> = all.iter().map(|s| s.id()).collect();
+    let names: Vec<String> = names.iter().map(|n| n.trim().to_lowercase()).collect();
+    if let Some(unknown) = names
+        .iter()
+        .find(|n| n.as_str() != "all" && !known.contains(&n.as_str()))
+    {
+        return Err(format!(
+            "unknown source {unknown:?}; available: all, {}",
+            known.join(", ")
+        ));
+    }
+    let wanted = |id: &str| {
+        if names.is_empty() {
+            default(id)
+        } else {
+            names.iter().any(|n| n == "all" || n == id)
+        }
+    };
+    Ok(all.into_iter().filter(|s| wanted(s.id())).collect())
+}
+
+/// Shared HTTP GET for HTML sources.
+pub fn fetch_html(
+    client: &Client,
+    url: &str,
+    site: &str,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let response = client
+        .get(url)
+        .header(
+            "Accept",
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+        )
+        .header("Accept-Language", "es-CO,es;q=0.9,en;q=0.8")
+        .header("DNT", "1")
+        .send()?;
+
+    if !response.status().is_success() {
+        eprintln!(
+            "Warning: {site} returned HTTP {} for {url}",
+            response.status()
+        );
+        return Ok(None);
+    }
+
+    Ok(Some(response.text()?))
+}
+
+/// Resolve `href` against the site root `base`.
+pub fn resolve_link(base: &str, href: &str) -> Option<String> {
+    if href.starts_with("http://") || href.starts_with("https://") {
+        Some(href.to_string())
+    } else if let Some(rest) = href.strip_prefix("//") {
+        Some(format!("https://{rest}"))
+    } else if href.starts_with('/') {
+        Some(format!("{}{href}", base.trim_end_matches('/')))
+    } else {
+        None
+    }
+}
+
+/// The host of an absolute URL, without a leading `www.`.
+pub(crate) fn host(url: &str) -> Option<&str> {
+    let rest = url.split_once("://")?.1;
+    let host = rest.split(['/', '?', '#', ':']).next()?;
+    Some(host.strip_prefix("www.").unwrap_or(host))
+}
+
+/// What marketplaces print when a search matches nothing, normalized.
+const NO_RESULTS_MESSAGES: &[&str] = &[
+    "no se encontraron resultados",
+    "no encontramos resultados",
+    "no hay resultados",
+    "no hay publicaciones que coincidan",
+    "no se encontraron vehiculos",
+    "no se encontraron inmuebles",
+    "sin resultados",
+    "0 resultados",
+];
+
+/// Whether the visible text of `body` says the search found nothing, so an
+/// empty page can be told apart from one whose layout changed.  Scripts are
+/// skipped: bundles carry these strings on every page.
+pub fn says_no_results(body: &str) -> bool {
+    let document = Html::parse_document(body);
+    let Ok(visible) = Selector::parse("body, body *:not(script):not(style):not(template)") else {
+        return false;
+    };
+    let text: Vec<&str> = document
+        .select(&visible)
+        .flat_map(|element| element.children().filter_map(|node| node.value().as_text()))
+        .map(|text| &**text)
+        .collect();
+    let text = format!(" {} ", text::normalize(&text.join(" ")));
+    NO_RESULTS_MESSAGES
+        .iter()
+        .any(|message| text.contains(&format!(" {message} ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> Settings {
+        Settings {
+            profile: Profile::embedded(),
+            mercadolibre_api: mercadolibre_api::DEFAULT_BASE_URL.to_string(),
+            mercadolibre_api_token: None,
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let ids = |names: &[&str]| {
+            let names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+            select(&names, settings()).map(|s| s.iter().map(|s| s.id()).collect::<Vec<_>>())
+        };
+        assert_eq!(ids(&[]), Ok(vec!["vendetunave"]));
+        assert_eq!(ids(&["all"]).unwrap().len(), registry(settings()).len());
+        assert_eq!(
+            ids(&["VendeTuNave", "vendetunave"]),
+            Ok(vec!["vendetunave"])
+        );
+        assert!(
+            ids(&["olx"])
+                .unwrap_err()
+                .contains("available: all, vendetunave")
+        );
+    }
+
+    #[test]
+    fn test_select_properties() {
+        let ids = |names: &[&str]| {
+            let names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+            select_properties(&names).map(|s| s.iter().map(|s| s.id()).collect::<Vec<_>>())
+        };
+        assert_eq!(ids(&[]), Ok(vec!["fincaraiz", "bodegasylocales"]));
+        assert_eq!(ids(&["bodegasylocales"]), Ok(vec!["bodegasylocales"]));
+        assert!(
+            ids(&["tucarro"])
+                .unwrap_err()
+                .contains("available: all, fincaraiz")
+        );
+    }
+
+    #[test]
+    fn test_resolve_link_and_handles() {
+        let base = "https://www.vendetunave.co";
+        assert_eq!(
+            resolve_link(base, "/vehiculo/1").as_deref(),
+            Some("https://www.vendetunave.co/vehiculo/1")
+        );
+        assert_eq!(
+            resolve_link(base, "//cdn.example/a.jpg").as_deref(),
+            Some("https://cdn.example/a.jpg")
+        );
+        assert_eq!(resolve_link(base, "#top"), None);
+
+        let source = vendetunave::VendeTuNave::new(Profile::embedded());
+        assert!(source.handles("https://vendetunave.co/vehiculo/1/x"));
+        assert!(!source.handles("https://carros.tucarro.com.co/x"));
+    }
+
+    #[test]
+    fn test_says_no_results() {
+        assert!(says_no_results(
+            "<html><body><main><p>No se encontraron resultados para \"Lada Niva\"</p></main></body></html>"
+        ));
+        assert!(says_no_results("<body><h2>0 resultados</h2></body>"));
+        assert!(!says_no_results("<body><h2>10 resultados</h2></body>"));
+        assert!(!says_no_results(
+            "<body><div id=\"app\"></div><script>const empty = \"Sin resultados\";</script></body>"
+        ));
+    }
+}
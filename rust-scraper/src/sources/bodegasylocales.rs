@@ -0,0 +1,138 @@
+//! bodegasylocales.com, commercial real estate: warehouses, shops, offices
+//! and lots.  Results are server-rendered cards; see `property_cards`.
+//!
+//! The card selectors in `LAYOUT` are unverified: the fixtures are
+//! hand-written, and no saved page has been checked against them yet.
+
+use scraper::Html;
+
+use super::property_cards::{self, CardLayout};
+use super::{ParsedPage, Source};
+use crate::gazetteer;
+use crate::property::PropertyListing;
+use crate::text::normalize;
+use crate::url_encode;
+
+pub const ID: &str = "bodegasylocales";
+pub const NAME: &str = "BodegasYLocales";
+pub const BASE_URL: &str = "https://www.bodegasylocales.com";
+
+/// City searched when the query names none, as the site's own search does.
+const DEFAULT_CITY: &str = "Medellín";
+
+const LAYOUT: CardLayout = CardLayout {
+    cards: &[
+        ".property-card",
+        ".listing-item",
+        ".result-item",
+        ".property-listing",
+        "[data-property]",
+    ],
+    title: "h2, h3, .title, .property-title",
+    price: ".price, .property-price, [data-price]",
+    link: "a[href*='/propiedad/'], a[href*='/inmueble/'], a[href]",
+    location: ".location, .address, .property-location",
+    area: ".area, .size, [data-area]",
+};
+
+pub struct BodegasYLocales;
+
+impl Source<PropertyListing> for BodegasYLocales {
+    fn id(&self) -> &'static str {
+        ID
+    }
+
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn base_url(&self) -> &str {
+        BASE_URL
+    }
+
+    /// `/buscar?q={query}&ciudad={city}`, the city being the municipality
+    /// the query names, e.g. "bodega Itagüí" searches `ciudad=itagui`.
+    fn search_url(&self, query: &str, page: usize) -> String {
+        let city = gazetteer::resolve(query)
+            .filter(|place| place.is_exact())
+            .map_or_else(|| DEFAULT_CITY.to_string(), |place| place.city);
+        let url = format!(
+            "{BASE_URL}/buscar?q={}&ciudad={}",
+            url_encode(query),
+            url_encode(&normalize(&city))
+        );
+        if page > 1 {
+            format!("{url}&pagina={page}")
+        } else {
+            url
+        }
+    }
+
+    fn parse_page(&self, body: &str, max_results: usize) -> ParsedPage<PropertyListing> {
+        let document = Html::parse_document(body);
+        property_cards::parse_cards(&document, &LAYOUT, NAME, BASE_URL, max_results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::listing::Extraction;
+    use crate::property::{Operation, PropertyType};
+
+    const RESULTS: &str = include_str!("../../fixtures/bodegasylocales_results.html");
+
+    #[test]
+    fn test_search_url() {
+        assert_eq!(
+            BodegasYLocales.search_url("bodega Itagüí", 1),
+            "https://www.bodegasylocales.com/buscar?q=bodega+Itag%C3%BC%C3%AD&ciudad=itagui"
+        );
+        assert_eq!(
+            BodegasYLocales.search_url("oficina Santa Marta", 1),
+            "https://www.bodegasylocales.com/buscar?q=oficina+Santa+Marta&ciudad=santa+marta"
+        );
+        assert_eq!(
+            BodegasYLocales.search_url("local", 3),
+            "https://www.bodegasylocales.com/buscar?q=local&ciudad=medellin&pagina=3"
+        );
+    }
+
+    #[test]
+    fn test_parse_results() {
+        let page = BodegasYLocales.parse_page(RESULTS, usize::MAX);
+        assert_eq!(page.matched.as_deref(), Some(".property-card"));
+        assert_eq!(page.skipped_empty_title, 1);
+        assert_eq!(
+            page.next_page.as_deref(),
+            Some("https://www.bodegasylocales.com/buscar?q=bodega&pagina=2")
+        );
+        let listings = &page.listings;
+        assert_eq!(listings.len(), 2);
+
+        let warehouse = &listings[0];
+        assert_eq!(warehouse.id.as_deref(), Some("48211"));
+        assert_eq!(warehouse.title, "Bodega en arriendo Itagüí");
+        assert_eq!(
+            warehouse.url,
+            "https://www.bodegasylocales.com/propiedad/bodega-en-arriendo-itagui-48211"
+        );
+        assert_eq!(warehouse.price.amount, Some(18_500_000));
+        assert_eq!(warehouse.operation, Some(Operation::Rent));
+        assert_eq!(warehouse.property_type, Some(PropertyType::Warehouse));
+        assert_eq!(warehouse.area_m2, Some(1200.0));
+        assert_eq!(warehouse.city.as_deref(), Some("Itagüí, Antioquia"));
+        assert_eq!(
+            warehouse.image_url.as_deref(),
+            Some("https://www.bodegasylocales.com/media/48211/portada.jpg")
+        );
+        assert_eq!(warehouse.extraction, Extraction::HtmlCard);
+
+        let shop = &listings[1];
+        assert_eq!(shop.id.as_deref(), Some("51877"));
+        assert_eq!(shop.operation, Some(Operation::Sale));
+        assert_eq!(shop.property_type, Some(PropertyType::Retail));
+        assert_eq!(shop.area_m2, Some(64.5));
+        assert_eq!(shop.estrato, Some(4));
+    }
+}
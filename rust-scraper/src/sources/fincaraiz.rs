@@ -0,0 +1,357 @@
+//! fincaraiz.com.co, Colombia's largest real-estate portal.  Results pages
+//! are Next.js; the listings are read from `__NEXT_DATA__`, whose nesting
+//! has changed between site releases, so the payload is searched for the
+//! array of listing objects rather than read at a fixed path.  The rendered
+//! cards are the fallback.
+
+use scraper::Html;
+use serde_json::Value;
+
+use super::property_cards::{self, CardLayout};
+use super::{ParsedPage, Source};
+use crate::health;
+use crate::listing::Extraction;
+use crate::next_data;
+use crate::price::Price;
+use crate::property::{self, Operation, PropertyListing, PropertyType};
+use crate::sources;
+use crate::text::normalize;
+
+pub const ID: &str = "fincaraiz";
+pub const NAME: &str = "FincaRaiz";
+pub const BASE_URL: &str = "https://www.fincaraiz.com.co";
+
+const LAYOUT: CardLayout = CardLayout {
+    cards: &[
+        ".MuiCard-root",
+        ".listingCard",
+        ".property-card",
+        "article[data-testid]",
+        "[data-listing-id]",
+        ".listing-card",
+    ],
+    title: "h2, h3, .title, [class*='title'], [class*='Title']",
+    price: "[class*='price'], [class*='Price'], span[class*='amount']",
+    link: "a[href*='/inmueble/'], a[href*='/bodega/'], a[href]",
+    location: "[class*='location'], [class*='address']",
+    area: "[class*='area'], [class*='size']",
+};
+
+/// Connectives dropped from the location along with the operation and
+/// property-type words.
+const CONNECTIVES: &[&str] = &["en", "de"];
+
+pub struct FincaRaiz;
+
+impl Source<PropertyListing> for FincaRaiz {
+    fn id(&self) -> &'static str {
+        ID
+    }
+
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn base_url(&self) -> &str {
+        BASE_URL
+    }
+
+    /// `/{operation}/{type}/{location}`, e.g. "bodega medellin" becomes
+    /// `/arriendo/bodegas/medellin`.  Rent is assumed unless the query says
+    /// "venta".
+    fn search_url(&self, query: &str, page: usize) -> String {
+        let operation = match property::operation(query) {
+            Some(Operation::Sale) => "venta",
+            _ => "arriendo",
+        };
+        let kind = property::property_type(query).map_or("inmuebles", type_slug);
+        let keywords = property::keyword_tokens(query);
+        let words = normalize(query).replace('.', " ");
+        let location: Vec<&str> = words
+            .split_whitespace()
+            .filter(|w| !CONNECTIVES.contains(w) && !keywords.contains(w))
+            .collect();
+        let mut url = format!("{BASE_URL}/{operation}/{kind}");
+        if !location.is_empty() {
+            url = format!("{url}/{}", location.join("-"));
+        }
+        if page > 1 {
+            url = format!("{url}/pagina{page}");
+        }
+        url
+    }
+
+    fn parse_page(&self, body: &str, max_results: usize) -> ParsedPage<PropertyListing> {
+        let document = Html::parse_document(body);
+        if let Some(payload) = next_data::payload(&document) {
+            let listings: Vec<PropertyListing> = listing_objects(&payload)
+                .into_iter()
+                .filter_map(json_listing)
+                .take(max_results)
+                .collect();
+            if !listings.is_empty() {
+                return ParsedPage {
+                    listings,
+                    matched: Some(health::NEXT_DATA_MATCH.to_string()),
+                    ..ParsedPage::empty()
+                };
+            }
+        }
+        property_cards::parse_cards(&document, &LAYOUT, NAME, BASE_URL, max_results)
+    }
+}
+
+/// The path segment the site uses for each type of property.
+fn type_slug(kind: PropertyType) -> &'static str {
+    match kind {
+        PropertyType::Apartment => "apartamentos",
+        PropertyType::Studio => "apartaestudios",
+        PropertyType::House => "casas",
+        PropertyType::Office => "oficinas",
+        PropertyType::Retail => "locales",
+        PropertyType::Warehouse => "bodegas",
+        PropertyType::Lot => "lotes",
+        PropertyType::Farm => "fincas",
+        PropertyType::MedicalOffice => "consultorios",
+        PropertyType::Building => "edificios",
+    }
+}
+
+/// The longest array in the payload whose objects look like listings: a
+/// title and a link each.
+fn listing_objects(payload: &Value) -> Vec<&Value> {
+    fn walk<'a>(value: &'a Value, best: &mut Vec<&'a Value>) {
+        match value {
+            Value::Array(items) => {
+                let listings: Vec<&Value> = items
+                    .iter()
+                    .filter(|item| str_at(item, &["title"]).is_some() && link(item).is_some())
+                    .collect();
+                if listings.len() > best.len() {
+                    *best = listings;
+                }
+                items.iter().for_each(|item| walk(item, best));
+            }
+            Value::Object(map) => map.values().for_each(|v| walk(v, best)),
+            _ => {}
+        }
+    }
+    let mut best = Vec::new();
+    walk(payload, &mut best);
+    best
+}
+
+/// A string (or number) at the first of `keys` that holds one.  A value
+/// that is an object is read through its `name`.
+fn str_at(item: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|key| match item.get(key)? {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Object(map) => map.get("name").and_then(Value::as_str).map(str::to_string),
+        Value::Array(items) => items
+            .first()
+            .and_then(|first| first.get("name").or(Some(first)))
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        _ => None,
+    })
+}
+
+fn link(item: &Value) -> Option<String> {
+    str_at(item, &["link", "url", "path", "href"])
+}
+
+/// A whole amount of pesos, which the payload sometimes writes as a float
+/// such as `2800000.0`.
+fn cop_amount(value: &Value) -> Option<u64> {
+    value.as_u64().or_else(|| {
+        value
+            .as_f64()
+            .filter(|n| n.is_finite() && *n >= 0.0)
+            .map(|n| n.round() as u64)
+    })
+}
+
+fn json_listing(item: &Value) -> Option<PropertyListing> {
+    let title = str_at(item, &["title"])?;
+    let url = sources::resolve_link(BASE_URL, &link(item)?)?;
+    let mut listing = PropertyListing::new(title, url, NAME, Extraction::NextData);
+    listing.id = str_at(item, &["id", "code", "fr_property_id"])
+        .or_else(|| property_cards::url_id(&listing.url));
+
+    listing.price = match item.get("price") {
+        Some(Value::Object(price)) => price
+            .get("amount")
+            .and_then(cop_amount)
+            .map_or_else(Price::missing, Price::from_cop),
+        Some(number @ Value::Number(_)) => {
+            cop_amount(number).map_or_else(Price::missing, Price::from_cop)
+        }
+        Some(Value::String(raw)) => Price::parse(raw),
+        _ => Price::missing(),
+    };
+    listing.area_m2 = ["m2", "area", "built_area", "m2Built"]
+        .iter()
+        .find_map(|key| item.get(key)?.as_f64())
+        .filter(|a| *a > 0.0);
+    listing.estrato = str_at(item, &["stratum", "estrato"])
+        .and_then(|s| {
+            s.trim_start_matches(|c: char| !c.is_ascii_digit())
+                .parse()
+                .ok()
+        })
+        .filter(|e| (1..=6).contains(e));
+    listing.property_type =
+        str_at(item, &["property_type", "propertyType"]).and_then(|t| property::property_type(&t));
+    listing.operation = str_at(item, &["operation_type", "operationType", "operation"])
+        .and_then(|t| property::operation(&t));
+
+    let locations = item.get("locations").unwrap_or(item);
+    listing.city = str_at(locations, &["city", "ciudad"]);
+    listing.neighbourhood = str_at(locations, &["neighbourhood", "neighborhood", "barrio"]);
+    if let Some((lat, lon)) = item
+        .get("latitude")
+        .and_then(Value::as_f64)
+        .zip(item.get("longitude").and_then(Value::as_f64))
+    {
+        (listing.latitude, listing.longitude) = (Some(lat), Some(lon));
+    }
+    listing.description = str_at(item, &["description"]);
+    listing.images = item
+        .get("images")
+        .and_then(Value::as_array)
+        .map(|images| {
+            images
+                .iter()
+                .filter_map(|image| {
+                    image
+                        .as_str()
+                        .or_else(|| image.get("image")?.as_str())
+                        .or_else(|| image.get("url")?.as_str())
+                })
+                .filter_map(|src| sources::resolve_link(BASE_URL, src))
+                .collect()
+        })
+        .unwrap_or_default();
+    listing.image_url = listing
+        .images
+        .first()
+        .cloned()
+        .or_else(|| str_at(item, &["img", "image"]));
+
+    let text = format!(
+        "{}\n{}",
+        listing.title,
+        listing.description.as_deref().unwrap_or_default()
+    );
+    listing.fill_from_text(&text);
+    listing.record_provenance(Extraction::NextData);
+    Some(listing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price::Currency;
+
+    const RESULTS: &str = include_str!("../../fixtures/fincaraiz_results.html");
+
+    #[test]
+    fn test_search_url() {
+        assert_eq!(
+            FincaRaiz.search_url("bodega Medellín", 1),
+            "https://www.fincaraiz.com.co/arriendo/bodegas/medellin"
+        );
+        assert_eq!(
+            FincaRaiz.search_url("apartamentos en venta Laureles Medellín", 2),
+            "https://www.fincaraiz.com.co/venta/apartamentos/laureles-medellin/pagina2"
+        );
+        assert_eq!(
+            FincaRaiz.search_url("se vende casa Envigado", 1),
+            "https://www.fincaraiz.com.co/venta/casas/envigado"
+        );
+        assert_eq!(
+            FincaRaiz.search_url("local comercial Medellín", 1),
+            "https://www.fincaraiz.com.co/arriendo/locales/medellin"
+        );
+        assert_eq!(
+            FincaRaiz.search_url("Bogotá", 1),
+            "https://www.fincaraiz.com.co/arriendo/inmuebles/bogota"
+        );
+    }
+
+    #[test]
+    fn test_parse_next_data() {
+        let page = FincaRaiz.parse_page(RESULTS, usize::MAX);
+        assert_eq!(page.matched.as_deref(), Some(health::NEXT_DATA_MATCH));
+        let listings = &page.listings;
+        assert_eq!(listings.len(), 3);
+
+        let apartment = &listings[0];
+        assert_eq!(apartment.id.as_deref(), Some("10041872"));
+        assert_eq!(apartment.title, "Apartamento en arriendo en Laureles");
+        assert_eq!(
+            apartment.url,
+            "https://www.fincaraiz.com.co/apartamento-en-arriendo/10041872"
+        );
+        assert_eq!(apartment.price.amount, Some(2_800_000));
+        assert_eq!(apartment.operation, Some(Operation::Rent));
+        assert_eq!(apartment.property_type, Some(PropertyType::Apartment));
+        assert_eq!(apartment.area_m2, Some(78.0));
+        assert_eq!(apartment.estrato, Some(5));
+        assert_eq!(apartment.city.as_deref(), Some("Medellín"));
+        assert_eq!(apartment.neighbourhood.as_deref(), Some("Laureles"));
+        assert_eq!(apartment.images.len(), 2);
+        assert_eq!(apartment.extraction, Extraction::NextData);
+
+        let warehouse = &listings[1];
+        assert_eq!(warehouse.property_type, Some(PropertyType::Warehouse));
+        assert_eq!(warehouse.area_m2, Some(1250.0));
+        assert_eq!(warehouse.estrato, None);
+
+        let house = &listings[2];
+        assert_eq!(house.operation, Some(Operation::Sale));
+        assert_eq!(house.price.amount, Some(890_000_000));
+        assert_eq!(house.price.currency, Currency::Cop);
+    }
+
+    #[test]
+    fn test_float_prices() {
+        let item = serde_json::json!({
+            "title": "Apartamento en arriendo",
+            "link": "/apartamento-en-arriendo/10041872",
+            "price": {"amount": 2800000.0},
+        });
+        assert_eq!(json_listing(&item).unwrap().price.amount, Some(2_800_000));
+        let item = serde_json::json!({
+            "title": "Casa en venta",
+            "link": "/casa-en-venta/10038450",
+            "price": 890000000.0,
+        });
+        assert_eq!(json_listing(&item).unwrap().price.amount, Some(890_000_000));
+    }
+
+    #[test]
+    fn test_parse_html_cards() {
+        let body = RESULTS.replace("__NEXT_DATA__", "next-data-removed");
+        let page = FincaRaiz.parse_page(&body, usize::MAX);
+        assert_eq!(page.matched.as_deref(), Some(".MuiCard-root"));
+        assert_eq!(
+            page.next_page.as_deref(),
+            Some("https://www.fincaraiz.com.co/arriendo/inmuebles/medellin/pagina2")
+        );
+        let listings = &page.listings;
+        assert_eq!(listings.len(), 3);
+
+        let apartment = &listings[0];
+        assert_eq!(apartment.id.as_deref(), Some("10041872"));
+        assert_eq!(apartment.price.amount, Some(2_800_000));
+        assert_eq!(apartment.operation, Some(Operation::Rent));
+        assert_eq!(apartment.property_type, Some(PropertyType::Apartment));
+        assert_eq!(apartment.area_m2, Some(78.0));
+        assert_eq!(apartment.estrato, Some(5));
+        assert_eq!(apartment.city.as_deref(), Some("Laureles, Medellín"));
+        assert_eq!(apartment.extraction, Extraction::HtmlCard);
+        assert_eq!(listings[1].area_m2, Some(1250.0));
+    }
+}
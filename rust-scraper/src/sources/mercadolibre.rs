@@ -0,0 +1,103 @@
+//! carros.mercadolibre.com.co, MercadoLibre Colombia's vehicle category.
+//! Same platform as TuCarro; see `polycard`.  No results page of this site
+//! has been saved yet: the poly-card parser is only checked against
+//! TuCarro's, and the legacy layout against hand-written markup.
+
+use super::polycard::{self, Site};
+use super::{ParsedPage, Source};
+
+pub const ID: &str = "mercadolibre";
+pub const NAME: &str = "MercadoLibre";
+pub const BASE_URL: &str = "https://carros.mercadolibre.com.co";
+
+const SITE: Site = Site {
+    name: NAME,
+    item_base: "https://articulo.mercadolibre.com.co",
+};
+
+pub struct MercadoLibre;
+
+impl Source for MercadoLibre {
+    fn id(&self) -> &'static str {
+        ID
+    }
+
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn base_url(&self) -> &str {
+        BASE_URL
+    }
+
+    fn search_url(&self, query: &str, page: usize) -> String {
+        polycard::search_url(BASE_URL, query, page)
+    }
+
+    fn parse_page(&self, body: &str, max_results: usize) -> ParsedPage {
+        polycard::parse_page(body, &SITE, max_results)
+    }
+
+    /// Results and item pages live on different subdomains.
+    fn handles(&self, url: &str) -> bool {
+        polycard::on_domain(url, "mercadolibre.com.co")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seller::SellerType;
+
+    /// Made-up cards in the older layout, not a saved page.
+    const LEGACY_CARDS: &str = include_str!("../../fixtures/legacy_cards_handwritten.html");
+
+    #[test]
+    fn test_search_url_and_handles() {
+        assert_eq!(
+            MercadoLibre.search_url("Renault Duster", 1),
+            "https://carros.mercadolibre.com.co/renault-duster_NoIndex_True"
+        );
+        assert_eq!(
+            MercadoLibre.search_url("Renault Duster", 2),
+            "https://carros.mercadolibre.com.co/renault-duster_Desde_49_NoIndex_True"
+        );
+        assert!(MercadoLibre.handles("https://articulo.mercadolibre.com.co/MCO-1-_JM"));
+        assert!(!MercadoLibre.handles("https://articulo.tucarro.com.co/MCO-1-_JM"));
+    }
+
+    #[test]
+    fn test_parse_hand_written_legacy_cards() {
+        let page = MercadoLibre.parse_page(LEGACY_CARDS, usize::MAX);
+        assert_eq!(
+            page.matched.as_deref(),
+            Some(polycard::LEGACY_CARD_SELECTOR)
+        );
+        let listings = &page.listings;
+        assert_eq!(listings.len(), 2);
+
+        let duster = &listings[0];
+        assert_eq!(duster.title, "Renault Duster 2.0 Dynamique 4x4");
+        assert_eq!(
+            duster.url,
+            "https://articulo.mercadolibre.com.co/MCO-1398765432-renault-duster-20-dynamique-4x4-_JM"
+        );
+        assert_eq!(duster.id.as_deref(), Some("MCO1398765432"));
+        assert_eq!(duster.price.amount, Some(54_900_000));
+        assert_eq!(duster.year, Some(2018));
+        assert_eq!(duster.mileage, Some(62_000));
+        assert_eq!(duster.city.as_deref(), Some("Suba - Bogotá D.C."));
+        assert_eq!(
+            duster.image_url.as_deref(),
+            Some("https://http2.mlstatic.com/D_NQ_NP_654321-MCO51234567890_052024-W.webp")
+        );
+
+        // Labelled attributes: año, kilómetros and ubicación.
+        let labelled = &listings[1];
+        assert_eq!(labelled.price.amount, Some(41_000_000));
+        assert_eq!(labelled.year, Some(2017));
+        assert_eq!(labelled.mileage, Some(80_500));
+        assert_eq!(labelled.city.as_deref(), Some("Pereira - Risaralda"));
+        assert_eq!(labelled.seller_type, Some(SellerType::Dealer));
+    }
+}
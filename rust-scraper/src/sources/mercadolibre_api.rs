@@ -0,0 +1,459 @@
+//! MercadoLibre's search API for site MCO, restricted to the cars and vans
+//! category.  Items carry typed attributes (BRAND, MODEL, VEHICLE_YEAR,
+//! KILOMETERS, ...) so nothing has to be read from markup.  The search
+//! refuses anonymous requests with HTTP 403; pass an access token with
+//! `--mercadolibre-api-token` or `MERCADOLIBRE_API_TOKEN`.
+
+use std::error::Error;
+
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+use super::{ParsedPage, Source};
+use crate::health;
+use crate::listing::{Extraction, Listing};
+use crate::mileage::{self, DistanceUnit};
+use crate::price::Price;
+use crate::seller::{self, SellerType};
+use crate::url_encode;
+use crate::year;
+
+pub const ID: &str = "mercadolibre-api";
+pub const NAME: &str = "MercadoLibre";
+pub const DEFAULT_BASE_URL: &str = "https://api.mercadolibre.com";
+
+/// "Carros y Camionetas" on site MCO.
+const CATEGORY: &str = "MCO1744";
+
+/// Items per request; the API's maximum.
+const PAGE_SIZE: usize = 50;
+
+/// The API answers no request whose `offset + limit` exceeds this.
+const MAX_OFFSET: usize = 1000;
+
+pub struct MercadoLibreApi {
+    /// API root, e.g. `https://api.mercadolibre.com` or a local mock.
+    base_url: String,
+    /// OAuth access token sent as `Authorization: Bearer`.
+    token: Option<String>,
+}
+
+impl MercadoLibreApi {
+    pub fn new(base_url: &str, token: Option<String>) -> Self {
+        MercadoLibreApi {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: token.filter(|t| !t.trim().is_empty()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    results: Vec<Item>,
+    paging: Option<Paging>,
+}
+
+#[derive(Deserialize)]
+struct Paging {
+    total: Option<usize>,
+    offset: usize,
+    limit: usize,
+}
+
+impl Paging {
+    /// Whether this is the last page the API will serve: the results ran
+    /// out, or the next page would pass `MAX_OFFSET`.
+    fn is_last(&self) -> bool {
+        let next = self.offset + self.limit;
+        self.total.is_some_and(|total| next >= total) || next + self.limit > MAX_OFFSET
+    }
+}
+
+#[derive(Deserialize)]
+struct Item {
+    id: Option<String>,
+    title: Option<String>,
+    price: Option<f64>,
+    currency_id: Option<String>,
+    permalink: Option<String>,
+    thumbnail: Option<String>,
+    #[serde(default)]
+    pictures: Vec<Picture>,
+    condition: Option<String>,
+    #[serde(default)]
+    attributes: Vec<Attribute>,
+    location: Option<Location>,
+    seller_address: Option<Location>,
+    seller: Option<Seller>,
+    official_store_id: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct Picture {
+    secure_url: Option<String>,
+    url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Attribute {
+    id: String,
+    value_name: Option<String>,
+    value_struct: Option<ValueStruct>,
+}
+
+/// A number with its unit, e.g. `{"number": 48000, "unit": "km"}`.
+#[derive(Deserialize)]
+struct ValueStruct {
+    number: Option<f64>,
+    unit: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Location {
+    city: Option<Named>,
+    state: Option<Named>,
+}
+
+#[derive(Deserialize)]
+struct Named {
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Seller {
+    car_dealer: Option<bool>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl Source for MercadoLibreApi {
+    fn id(&self) -> &'static str {
+        ID
+    }
+
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn search_url(&self, query: &str, page: usize) -> String {
+        let offset = page.saturating_sub(1) * PAGE_SIZE;
+        format!(
+            "{}/sites/MCO/search?category={CATEGORY}&q={}&offset={offset}&limit={PAGE_SIZE}",
+            self.base_url,
+            url_encode(query)
+        )
+    }
+
+    fn parse_page(&self, body: &str, max_results: usize) -> ParsedPage {
+        let Ok(response) = serde_json::from_str::<SearchResponse>(body) else {
+            return ParsedPage::empty();
+        };
+        ParsedPage {
+            listings: response
+                .results
+                .into_iter()
+                .filter_map(Item::into_listing)
+                .take(max_results)
+                .collect(),
+            matched: Some(health::API_MATCH.to_string()),
+            last_page: response.paging.is_some_and(|p| p.is_last()),
+            ..ParsedPage::empty()
+        }
+    }
+
+    fn fetch(&self, client: &Client, url: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let mut request = client.get(url).header("Accept", "application/json");
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send()?;
+        if !response.status().is_success() {
+            eprintln!(
+                "Warning: MercadoLibre API returned HTTP {} for {url}",
+                response.status()
+            );
+            if self.token.is_none() && response.status() == reqwest::StatusCode::FORBIDDEN {
+                eprintln!("Hint: the search needs --mercadolibre-api-token");
+            }
+            return Ok(None);
+        }
+        Ok(Some(response.text()?))
+    }
+}
+
+impl Item {
+    /// Convert into a `Listing`.  Items without a title or link are dropped.
+    fn into_listing(self) -> Option<Listing> {
+        let title = self.title.filter(|t| !t.trim().is_empty())?;
+        let url = self.permalink.filter(|u| !u.is_empty())?;
+        let attribute = |id: &str| self.attributes.iter().find(|a| a.id == id);
+        let name = |id: &str| {
+            attribute(id)
+                .and_then(|a| a.value_name.clone())
+                .filter(|v| !v.trim().is_empty())
+        };
+
+        let mut listing = Listing::new(title, url, NAME, Extraction::Api);
+        listing.id = self.id;
+        listing.price = match (self.price, self.currency_id.as_deref()) {
+            (Some(amount), Some("COP") | None) => Price::from_cop(amount.round() as u64),
+            (Some(amount), Some(currency)) => Price::parse(&format!("{currency} {amount}")),
+            (None, _) => Price::missing(),
+        };
+        listing.year = name("VEHICLE_YEAR")
+            .and_then(|y| y.trim().parse::<u32>().ok())
+            .filter(|y| (1900..=year::max_year()).contains(y));
+        listing.year_confidence = listing.year.map(|_| year::STRUCTURED_CONFIDENCE);
+        let reading = attribute("KILOMETERS").and_then(kilometers);
+        listing.mileage = reading.map(|m| m.km);
+        listing.mileage_unit = reading.map(|m| m.unit);
+        listing.brand = name("BRAND");
+        listing.model = name("MODEL");
+        listing.fuel = name("FUEL_TYPE");
+        listing.transmission = name("TRANSMISSION");
+        listing.condition = name("ITEM_CONDITION").or(self.condition);
+        listing.city = self
+            .location
+            .as_ref()
+            .and_then(Location::label)
+            .or_else(|| self.seller_address.as_ref().and_then(Location::label));
+        listing.images = self
+            .pictures
+            .into_iter()
+            .filter_map(|p| p.secure_url.or(p.url))
+            .map(|u| secure(&u))
+            .collect();
+        listing.image_url = listing
+            .images
+            .first()
+            .cloned()
+            .or_else(|| self.thumbnail.map(|t| secure(&t)));
+        if listing.images.is_empty() {
+            listing.images.extend(listing.image_url.clone());
+        }
+        let dealer = self.seller.as_ref().and_then(|s| {
+            s.car_dealer
+                .or_else(|| s.tags.iter().any(|t| t == "car_dealer").then_some(true))
+        });
+        listing.seller_type = if self.official_store_id.is_some() {
+            Some(SellerType::Dealer)
+        } else {
+            seller::from_flags(None, dealer)
+        };
+        listing.record_provenance(Extraction::Api);
+        Some(listing)
+    }
+}
+
+impl Location {
+    /// "City - State", as the site shows it.
+    fn label(&self) -> Option<String> {
+        let name = |n: &Option<Named>| n.as_ref()?.name.clone().filter(|s| !s.trim().is_empty());
+        match (name(&self.city), name(&self.state)) {
+            (Some(city), Some(state)) => Some(format!("{city} - {state}")),
+            (city, state) => city.or(state),
+        }
+    }
+}
+
+/// The KILOMETERS attribute: its typed number when present, else its
+/// display value ("48.000 km").
+fn kilometers(attribute: &Attribute) -> Option<mileage::Mileage> {
+    if let Some(ValueStruct {
+        number: Some(number),
+        unit,
+    }) = &attribute.value_struct
+    {
+        let miles = unit.as_deref().is_some_and(|u| u.starts_with("mi"));
+        let km = if miles {
+            number * mileage::KM_PER_MILE
+        } else {
+            *number
+        };
+        return Some(mileage::Mileage {
+            km: km.round() as u32,
+            unit: if miles {
+                DistanceUnit::Miles
+            } else {
+                DistanceUnit::Km
+            },
+        });
+    }
+    let value = attribute.value_name.as_deref()?;
+    mileage::extract(value).or_else(|| mileage::extract(&format!("{value} km")))
+}
+
+/// Thumbnails are served over plain HTTP by default.
+fn secure(url: &str) -> String {
+    match url.strip_prefix("http://") {
+        Some(rest) => format!("https://{rest}"),
+        None => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+    use crate::http_client;
+
+    /// Synthetic: shaped after the documented search response, with made-up
+    /// ids.  It has not been checked against a live response, which needs
+    /// an access token.
+    const RESPONSE: &str = r#"{
+        "site_id": "MCO",
+        "paging": {"total": 2350, "offset": 50, "limit": 50},
+        "results": [
+            {
+                "id": "MCO1512345678",
+                "title": "Mazda 3 Grand Touring 2.0 At",
+                "price": 72500000,
+                "currency_id": "COP",
+                "permalink": "https://articulo.mercadolibre.com.co/MCO-1512345678-mazda-3-grand-touring-20-at-_JM",
+                "thumbnail": "http://http2.mlstatic.com/D_612345-MCO81234567890_012026-I.jpg",
+                "condition": "used",
+                "attributes": [
+                    {"id": "BRAND", "value_name": "Mazda"},
+                    {"id": "MODEL", "value_name": "3"},
+                    {"id": "VEHICLE_YEAR", "value_name": "2019"},
+                    {"id": "KILOMETERS", "value_name": "48000 km",
+                     "value_struct": {"number": 48000, "unit": "km"}},
+                    {"id": "FUEL_TYPE", "value_name": "Gasolina"},
+                    {"id": "TRANSMISSION", "value_name": "Automática"}
+                ],
+                "location": {
+                    "city": {"id": "TUNPQ0VOVmIyMzc", "name": "Envigado"},
+                    "state": {"id": "TUNPUEFOVDUzNzM", "name": "Antioquia"}
+                },
+                "seller": {"id": 123, "car_dealer": false}
+            },
+            {
+                "id": "MCO2887654321",
+                "title": "Mazda CX-30 Touring",
+                "price": 21500,
+                "currency_id": "USD",
+                "permalink": "https://articulo.mercadolibre.com.co/MCO-2887654321-mazda-cx-30-touring-_JM",
+                "attributes": [{"id": "KILOMETERS", "value_name": "12.000 km"}],
+                "seller_address": {"city": {"name": "Bogotá"}, "state": {"name": "Bogotá D.C."}},
+                "official_store_id": 4321
+            },
+            {"id": "MCO1", "title": "", "permalink": "https://articulo.mercadolibre.com.co/MCO-1-_JM"}
+        ]
+    }"#;
+
+    /// Serve `body` to a single request and return the base URL and a
+    /// handle yielding the request line and headers.
+    fn mock_server(body: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind a local port");
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept the request");
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            // Headers end at the first blank line.
+            while reader.read_line(&mut request).unwrap() > 2 {}
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            request
+        });
+        (base, handle)
+    }
+
+    #[test]
+    fn test_search_url() {
+        let api = MercadoLibreApi::new("https://api.mercadolibre.com/", None);
+        assert_eq!(
+            api.search_url("Mazda 3", 1),
+            "https://api.mercadolibre.com/sites/MCO/search?category=MCO1744&q=Mazda+3&offset=0&limit=50"
+        );
+        assert!(api.search_url("Mazda 3", 3).contains("&offset=100&"));
+    }
+
+    #[test]
+    fn test_search_against_mock_server() {
+        let (base, server) = mock_server(RESPONSE);
+        let api = MercadoLibreApi::new(&base, Some("APP_USR-test".to_string()));
+        let client = http_client().unwrap();
+        let body = api
+            .fetch(&client, &api.search_url("mazda 3", 2))
+            .unwrap()
+            .expect("the mock answers 200");
+        let request = server.join().unwrap();
+        assert!(
+            request.starts_with(
+                "GET /sites/MCO/search?category=MCO1744&q=mazda+3&offset=50&limit=50 "
+            )
+        );
+        assert!(
+            request
+                .to_lowercase()
+                .contains("authorization: bearer app_usr-test\r\n")
+        );
+
+        let page = api.parse_page(&body, usize::MAX);
+        assert_eq!(page.matched.as_deref(), Some(health::API_MATCH));
+        assert!(!page.last_page);
+        let listings = &page.listings;
+        assert_eq!(listings.len(), 2);
+
+        let mazda = &listings[0];
+        assert_eq!(mazda.id.as_deref(), Some("MCO1512345678"));
+        assert_eq!(mazda.price.amount, Some(72_500_000));
+        assert_eq!(mazda.brand.as_deref(), Some("Mazda"));
+        assert_eq!(mazda.model.as_deref(), Some("3"));
+        assert_eq!(mazda.year, Some(2019));
+        assert_eq!(mazda.mileage, Some(48_000));
+        assert_eq!(mazda.fuel.as_deref(), Some("Gasolina"));
+        assert_eq!(mazda.transmission.as_deref(), Some("Automática"));
+        assert_eq!(mazda.condition.as_deref(), Some("used"));
+        assert_eq!(mazda.city.as_deref(), Some("Envigado - Antioquia"));
+        assert_eq!(
+            mazda.image_url.as_deref(),
+            Some("https://http2.mlstatic.com/D_612345-MCO81234567890_012026-I.jpg")
+        );
+        assert_eq!(mazda.seller_type, Some(SellerType::Private));
+        assert_eq!(mazda.extraction, Extraction::Api);
+        assert_eq!(mazda.provenance["year"], Extraction::Api);
+
+        let cx30 = &listings[1];
+        assert_eq!(cx30.price.currency, crate::price::Currency::Usd);
+        assert_eq!(cx30.mileage, Some(12_000));
+        assert_eq!(cx30.city.as_deref(), Some("Bogotá - Bogotá D.C."));
+        assert_eq!(cx30.seller_type, Some(SellerType::Dealer));
+    }
+
+    #[test]
+    fn test_paging_stops_at_the_offset_cap() {
+        let paging = |offset, total| Paging {
+            total: Some(total),
+            offset,
+            limit: PAGE_SIZE,
+        };
+        assert!(!paging(900, 5000).is_last());
+        assert!(paging(950, 5000).is_last());
+        assert!(paging(50, 100).is_last());
+        let api = MercadoLibreApi::new(DEFAULT_BASE_URL, None);
+        let body = r#"{"paging": {"total": 5000, "offset": 950, "limit": 50}, "results": []}"#;
+        assert!(api.parse_page(body, 10).last_page);
+    }
+
+    #[test]
+    fn test_parse_page_rejects_non_json() {
+        let api = MercadoLibreApi::new(DEFAULT_BASE_URL, None);
+        let page = api.parse_page("<html>Too many requests</html>", 10);
+        assert!(page.listings.is_empty());
+        assert_eq!(page.matched, None);
+    }
+}
@@ -0,0 +1,401 @@
+//! Results pages of MercadoLibre's classifieds platform, which TuCarro runs
+//! on.  Each result is a "poly-card"; the same cards are serialized in the
+//! preloaded state the page hydrates from, which is read first, with the
+//! rendered markup (poly-cards, or the older `ui-search` cards) as the
+//! fallback.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use serde_json::Value;
+
+use super::ParsedPage;
+use crate::health;
+use crate::images;
+use crate::listing::{Extraction, Listing};
+use crate::mileage;
+use crate::price::Price;
+use crate::seller::{self, SellerType};
+use crate::sources;
+use crate::text::normalize;
+use crate::year;
+
+/// Card selectors recorded in the health report for the HTML fallback.
+pub const CARD_SELECTOR: &str = "div.poly-card";
+pub const LEGACY_CARD_SELECTOR: &str = "li.ui-search-layout__item";
+
+/// Where the platform serves photos, by picture id.
+const IMAGE_BASE: &str = "https://http2.mlstatic.com/D_Q_NP_2X_";
+
+/// Prefix of the script holding the preloaded state.
+const STATE_PREFIX: &str = "_n.ctx.r=";
+
+/// One site on the platform.
+pub struct Site {
+    /// Name recorded in `Listing::source`.
+    pub name: &'static str,
+    /// Host of item pages, e.g. `https://articulo.tucarro.com.co`.  Promoted
+    /// cards link through a click tracker, so their URL is rebuilt from it.
+    pub item_base: &'static str,
+}
+
+/// Parse a results page: the preloaded state first, then the card markup.
+pub fn parse_page(body: &str, site: &Site, max_results: usize) -> ParsedPage {
+    let document = Html::parse_document(body);
+    if let Some(state) = preloaded_state(&document) {
+        let listings: Vec<Listing> = state_cards(&state)
+            .into_iter()
+            .filter_map(|card| card_listing(card, site))
+            .take(max_results)
+            .collect();
+        if !listings.is_empty() {
+            return ParsedPage {
+                listings,
+                matched: Some(health::PRELOADED_STATE_MATCH.to_string()),
+                next_page: state_next_page(&state),
+                ..ParsedPage::empty()
+            };
+        }
+    }
+    parse_html_cards(&document, site, max_results)
+}
+
+/// The `initialState` of the page's `__NORDIC_RENDERING_CTX__` script,
+/// which assigns a JSON object and then more JavaScript.
+pub fn preloaded_state(document: &Html) -> Option<Value> {
+    let sel = Selector::parse("script#__NORDIC_RENDERING_CTX__").ok()?;
+    let raw = document.select(&sel).next()?.text().collect::<String>();
+    let json = &raw[raw.find(STATE_PREFIX)? + STATE_PREFIX.len()..];
+    let context = serde_json::Deserializer::from_str(json)
+        .into_iter::<Value>()
+        .next()?
+        .ok()?;
+    context.pointer("/appProps/pageProps/initialState").cloned()
+}
+
+/// Every poly-card in the results, including those grouped into
+/// interventions such as promoted carousels.
+fn state_cards(state: &Value) -> Vec<&Value> {
+    let Some(results) = state.get("results").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    results
+        .iter()
+        .flat_map(
+            |result| match result.get("items").and_then(Value::as_array) {
+                Some(items) => items.iter().collect(),
+                None => vec![result],
+            },
+        )
+        .filter_map(|result| result.get("polycard"))
+        .collect()
+}
+
+fn state_next_page(state: &Value) -> Option<String> {
+    let next = state.pointer("/pagination/next_page")?;
+    if next.get("show").and_then(Value::as_bool) == Some(false) {
+        return None;
+    }
+    let url = next.get("url")?.as_str()?;
+    url.starts_with("http").then(|| url.to_string())
+}
+
+/// Convert one serialized poly-card.  Cards without a title are dropped.
+fn card_listing(card: &Value, site: &Site) -> Option<Listing> {
+    let component = |kind: &str| {
+        card.get("components")?
+            .as_array()?
+            .iter()
+            .find(|c| c.get("type").and_then(Value::as_str) == Some(kind))?
+            .get(kind)
+    };
+    let string = |value: Option<&Value>| {
+        value
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+    };
+
+    let title = string(component("title").and_then(|t| t.get("text")))?;
+    let metadata = card.get("metadata");
+    let id = string(metadata.and_then(|m| m.get("id")));
+    let promoted = string(metadata.and_then(|m| m.get("is_pad"))).as_deref() == Some("true");
+    let url = string(metadata.and_then(|m| m.get("url")))
+        .map(|url| {
+            if url.contains("://") {
+                url
+            } else {
+                format!("https://{url}")
+            }
+        })
+        .filter(|url| !promoted && !is_click_tracker(url))
+        .or_else(|| id.as_deref().and_then(|id| item_url(site, id)))
+        .map(|url| strip_fragment(&url))
+        .unwrap_or_default();
+
+    let attributes: Vec<String> = component("attributes_list")
+        .and_then(|a| a.get("texts"))
+        .and_then(Value::as_array)
+        .map(|texts| texts.iter().filter_map(|t| string(Some(t))).collect())
+        .unwrap_or_default();
+
+    let mut listing = Listing::new(title, url, site.name, Extraction::PreloadedState);
+    listing.id = id;
+    listing.price = component("price")
+        .and_then(|p| p.get("current_price"))
+        .map_or_else(Price::missing, state_price);
+    listing.city = string(component("location").and_then(|l| l.get("text")));
+    set_attributes(&mut listing, &attributes);
+    listing.images = card
+        .pointer("/pictures/pictures")
+        .and_then(Value::as_array)
+        .map(|pictures| {
+            pictures
+                .iter()
+                .filter_map(|p| p.get("id")?.as_str())
+                .map(|id| format!("{IMAGE_BASE}{id}-E.webp"))
+                .collect()
+        })
+        .unwrap_or_default();
+    listing.image_url = listing.images.first().cloned();
+    listing.seller_type = component("seller").and_then(state_seller_type);
+    listing.record_provenance(Extraction::PreloadedState);
+    Some(listing)
+}
+
+fn state_price(price: &Value) -> Price {
+    let Some(amount) = price.get("value").and_then(Value::as_f64) else {
+        return Price::missing();
+    };
+    match price.get("currency").and_then(Value::as_str) {
+        Some("COP") | None => Price::from_cop(amount.round() as u64),
+        Some(currency) => Price::parse(&format!("{currency} {amount}")),
+    }
+}
+
+/// The seller line is a template such as "Audi Colwagen {icon_cockade}";
+/// the icon's alt text ("Tienda oficial") is what marks a dealer.
+fn state_seller_type(seller: &Value) -> Option<SellerType> {
+    let mut text = seller.get("text")?.as_str()?.to_string();
+    for value in seller
+        .get("values")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let key = value.get("key").and_then(Value::as_str).unwrap_or_default();
+        let alt = value
+            .pointer("/icon/alt_text")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        text = text.replace(&format!("{{{key}}}"), alt);
+    }
+    seller::classify(&text)
+}
+
+/// The markup of one card layout.  Poly-cards are current; the older
+/// `ui-search` cards are still served on some MercadoLibre listings.
+struct CardLayout {
+    card: &'static str,
+    link: &'static str,
+    title: &'static str,
+    price: &'static str,
+    attributes: &'static str,
+    location: &'static str,
+    seller: &'static str,
+}
+
+const LAYOUTS: &[CardLayout] = &[
+    CardLayout {
+        card: CARD_SELECTOR,
+        link: "a.poly-component__title",
+        title: "a.poly-component__title",
+        price: ".poly-price__current .andes-money-amount",
+        attributes: "li.poly-attributes_list__item",
+        location: ".poly-component__location",
+        seller: ".poly-component__seller",
+    },
+    CardLayout {
+        card: LEGACY_CARD_SELECTOR,
+        link: "a.ui-search-link",
+        title: ".ui-search-item__title",
+        price: ".ui-search-price__second-line .andes-money-amount",
+        attributes: "li.ui-search-card-attributes__attribute",
+        location: ".ui-search-item__location",
+        seller: ".ui-search-official-store-label",
+    },
+];
+
+/// Fallback: read the rendered cards of the first layout on the page.
+fn parse_html_cards(document: &Html, site: &Site, max_results: usize) -> ParsedPage {
+    let mut page = ParsedPage::empty();
+    let Some((layout, card_sel)) = LAYOUTS.iter().find_map(|layout| {
+        let sel = Selector::parse(layout.card).ok()?;
+        document
+            .select(&sel)
+            .next()
+            .is_some()
+            .then_some((layout, sel))
+    }) else {
+        return page;
+    };
+    page.matched = Some(layout.card.to_string());
+
+    for card in document.select(&card_sel) {
+        if page.listings.len() >= max_results {
+            break;
+        }
+        let Some(title) = text(first(&card, layout.title)) else {
+            page.skipped_empty_title += 1;
+            continue;
+        };
+        let url = first(&card, layout.link)
+            .and_then(|a| a.value().attr("href"))
+            .and_then(|href| sources::resolve_link(site.item_base, href))
+            .map(|url| strip_fragment(&url))
+            .unwrap_or_default();
+
+        let mut listing = Listing::new(title, url, site.name, Extraction::HtmlCard);
+        listing.id = item_id(&listing.url);
+        // The aria-label carries the plain amount ("65900000 pesos
+        // colombianos"); the visible fraction is the fallback.
+        let amount = first(&card, layout.price);
+        listing.price = amount
+            .and_then(|a| a.value().attr("aria-label"))
+            .map(Price::parse)
+            .or_else(|| text(amount).map(|t| Price::parse(&t)))
+            .unwrap_or_else(Price::missing);
+        listing.city = text(first(&card, layout.location));
+        let attributes: Vec<String> = Selector::parse(layout.attributes)
+            .map(|sel| card.select(&sel).filter_map(|li| text(Some(li))).collect())
+            .unwrap_or_default();
+        set_attributes(&mut listing, &attributes);
+        listing.images = images::collect(&card, |src| sources::resolve_link(site.item_base, src));
+        listing.image_url = listing.images.first().cloned();
+        listing.seller_type = first(&card, layout.seller).and_then(|seller| {
+            let icons = Selector::parse("[aria-label]").ok()?;
+            let badges = seller
+                .select(&icons)
+                .filter_map(|icon| icon.value().attr("aria-label"));
+            let line = text(Some(seller))
+                .into_iter()
+                .chain(badges.map(str::to_string));
+            seller::classify(&line.collect::<Vec<_>>().join(" "))
+        });
+        listing.record_provenance(Extraction::HtmlCard);
+        page.listings.push(listing);
+    }
+    page
+}
+
+/// The first element under `card` matching `css`.
+fn first<'a>(card: &ElementRef<'a>, css: &str) -> Option<ElementRef<'a>> {
+    let sel = Selector::parse(css).ok()?;
+    card.select(&sel).next()
+}
+
+/// An element's text with whitespace collapsed, if it has any.
+fn text(element: Option<ElementRef>) -> Option<String> {
+    let text = element?.text().collect::<Vec<_>>().join(" ");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Year, mileage and, when the card has no location line, the city from a
+/// card's attribute list.  Entries are either bare (`["2023", "16.500 Km"]`)
+/// or labelled (`"Año: 2019"`, `"Kilómetros: 45.000"`, `"Ubicación:
+/// Medellín"`); a bare in-range year is the site's own field.
+fn set_attributes(listing: &mut Listing, attributes: &[String]) {
+    let as_year = |value: &str| {
+        value
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|y| (1900..=year::max_year()).contains(y))
+    };
+    let mut model_year = None;
+    let mut reading = None;
+    for attribute in attributes {
+        let (label, value) = match attribute.split_once(':') {
+            Some((label, value)) => (normalize(label), value.trim()),
+            None => (String::new(), attribute.trim()),
+        };
+        match label.as_str() {
+            "ano" | "modelo" => model_year = model_year.or_else(|| as_year(value)),
+            "kilometros" | "kilometraje" | "recorrido" => {
+                reading = reading.or_else(|| {
+                    mileage::extract(value).or_else(|| mileage::extract(&format!("{value} km")))
+                });
+            }
+            "ubicacion" if !value.is_empty() => {
+                listing.city = listing.city.take().or_else(|| Some(value.to_string()));
+            }
+            _ => {
+                model_year = model_year.or_else(|| as_year(value));
+                reading = reading.or_else(|| mileage::extract(value));
+            }
+        }
+    }
+    match model_year {
+        Some(y) => {
+            listing.year = Some(y);
+            listing.year_confidence = Some(year::STRUCTURED_CONFIDENCE);
+        }
+        None => {
+            let found = year::extract(&listing.title, None);
+            listing.year = found.map(|y| y.year);
+            listing.year_confidence = found.map(|y| y.confidence);
+        }
+    }
+    listing.mileage = reading.map(|m| m.km);
+    listing.mileage_unit = reading.map(|m| m.unit);
+}
+
+fn id_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b(MC[A-Z])-?(\d{6,})").expect("item id pattern is valid"))
+}
+
+/// The platform's item id ("MCO3558000760") in an item URL or id.
+pub fn item_id(url: &str) -> Option<String> {
+    let cap = id_re().captures(url)?;
+    Some(format!("{}{}", &cap[1], &cap[2]))
+}
+
+/// Canonical item page for an id; the slug is optional on the platform.
+fn item_url(site: &Site, id: &str) -> Option<String> {
+    let cap = id_re().captures(id)?;
+    Some(format!("{}/{}-{}-_JM", site.item_base, &cap[1], &cap[2]))
+}
+
+/// Whether `url` is on `domain` or one of its subdomains.
+pub fn on_domain(url: &str, domain: &str) -> bool {
+    sources::host(url)
+        .is_some_and(|h| h == domain || h.strip_suffix(domain).is_some_and(|s| s.ends_with('.')))
+}
+
+fn is_click_tracker(url: &str) -> bool {
+    url.contains("://click1.") || url.contains("/clicks/")
+}
+
+fn strip_fragment(url: &str) -> String {
+    url.split('#').next().unwrap_or(url).to_string()
+}
+
+/// Results per page; later pages start at `_Desde_<offset>`.
+const PAGE_SIZE: usize = 48;
+
+/// Search URL for one results page on a platform site: the query as a
+/// path slug, with `_NoIndex_True` as the site's own links carry it.
+pub fn search_url(base: &str, query: &str, page: usize) -> String {
+    let slug = normalize(&query.replace('.', " ")).replace(' ', "-");
+    let base = base.trim_end_matches('/');
+    if page > 1 {
+        let offset = (page - 1) * PAGE_SIZE + 1;
+        format!("{base}/{slug}_Desde_{offset}_NoIndex_True")
+    } else {
+        format!("{base}/{slug}_NoIndex_True")
+    }
+}
@@ -0,0 +1,138 @@
+//! Rendered result cards of the real-estate sites.  Both sites lay a card
+//! out as a title, a price line, a location line and a list of features;
+//! only the selectors differ.  Type, operation, area and estrato are read
+//! from the card text when no dedicated element carries them.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+
+use super::ParsedPage;
+use crate::images;
+use crate::listing::Extraction;
+use crate::price::Price;
+use crate::property::{self, PropertyListing};
+use crate::sources;
+
+/// Attributes that carry a listing's own id on the card element.
+const ID_ATTRIBUTES: &[&str] = &["data-listing-id", "data-property-id", "data-id"];
+
+/// One site's card markup.  `cards` are tried in order and the first that
+/// matches anything is used for the whole page.
+pub struct CardLayout {
+    pub cards: &'static [&'static str],
+    pub title: &'static str,
+    pub price: &'static str,
+    pub link: &'static str,
+    pub location: &'static str,
+    pub area: &'static str,
+}
+
+/// Read the cards of `layout` on `document`.  Links and photos are resolved
+/// against `base_url`; `source` is recorded on each listing.
+pub fn parse_cards(
+    document: &Html,
+    layout: &CardLayout,
+    source: &str,
+    base_url: &str,
+    max_results: usize,
+) -> ParsedPage<PropertyListing> {
+    let mut page = ParsedPage::empty();
+    let Some((matched, card_sel)) = layout.cards.iter().find_map(|css| {
+        let sel = Selector::parse(css).ok()?;
+        document.select(&sel).next().is_some().then_some((css, sel))
+    }) else {
+        return page;
+    };
+    page.matched = Some(matched.to_string());
+    page.next_page = next_page_link(document, base_url);
+
+    for card in document.select(&card_sel) {
+        if page.listings.len() >= max_results {
+            break;
+        }
+        let Some(title) = text(first(&card, layout.title)) else {
+            page.skipped_empty_title += 1;
+            continue;
+        };
+        let url = first(&card, layout.link)
+            .or_else(|| (card.value().name() == "a").then_some(card))
+            .and_then(|a| a.value().attr("href"))
+            .and_then(|href| sources::resolve_link(base_url, href))
+            .unwrap_or_default();
+
+        let mut listing = PropertyListing::new(title, url, source, Extraction::HtmlCard);
+        listing.id = ID_ATTRIBUTES
+            .iter()
+            .find_map(|attr| card.value().attr(attr))
+            .map(str::to_string)
+            .or_else(|| url_id(&listing.url));
+        listing.price = text(first(&card, layout.price))
+            .map(|t| Price::parse(&t))
+            .unwrap_or_else(Price::missing);
+        listing.city = text(first(&card, layout.location));
+        listing.area_m2 = text(first(&card, layout.area)).and_then(|t| property::area_m2(&t));
+        let card_text = text(Some(card)).unwrap_or_default();
+        listing.fill_from_text(&format!("{}\n{card_text}", listing.title));
+        listing.images = images::collect(&card, |src| sources::resolve_link(base_url, src));
+        listing.image_url = listing.images.first().cloned();
+        listing.record_provenance(Extraction::HtmlCard);
+        page.listings.push(listing);
+    }
+    page
+}
+
+/// The `rel="next"` link of a results page.
+fn next_page_link(document: &Html, base_url: &str) -> Option<String> {
+    let sel = Selector::parse(r#"a[rel="next"], link[rel="next"]"#).ok()?;
+    document
+        .select(&sel)
+        .find_map(|a| a.value().attr("href"))
+        .and_then(|href| sources::resolve_link(base_url, href))
+}
+
+fn url_id_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(\d{5,})/?(?:[?#]|$)").expect("url id pattern is valid"))
+}
+
+/// The numeric id most listing URLs end with, e.g. `.../apartamento/10012345`.
+pub fn url_id(url: &str) -> Option<String> {
+    Some(url_id_re().captures(url)?[1].to_string())
+}
+
+/// The first element under `card` matching `css`.
+fn first<'a>(card: &ElementRef<'a>, css: &str) -> Option<ElementRef<'a>> {
+    let sel = Selector::parse(css).ok()?;
+    card.select(&sel).next()
+}
+
+/// An element's text with whitespace collapsed, if it has any.
+fn text(element: Option<ElementRef>) -> Option<String> {
+    let text = element?.text().collect::<Vec<_>>().join(" ");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_id() {
+        assert_eq!(
+            url_id("https://www.fincaraiz.com.co/apartamento-en-arriendo/10012345").as_deref(),
+            Some("10012345")
+        );
+        assert_eq!(
+            url_id("https://www.bodegasylocales.com/propiedad/bodega-itagui-48211/?ref=list")
+                .as_deref(),
+            Some("48211")
+        );
+        assert_eq!(
+            url_id("https://www.fincaraiz.com.co/arriendo/bodegas"),
+            None
+        );
+    }
+}
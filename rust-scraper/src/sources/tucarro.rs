@@ -0,0 +1,211 @@
+//! tucarro.com.co, MercadoLibre's vehicle classifieds.  Results pages are
+//! server-rendered poly-cards with a preloaded state, so no browser is
+//! needed; see `polycard`.
+
+use super::polycard::{self, Site};
+use super::{ParsedPage, Source};
+
+pub const ID: &str = "tucarro";
+pub const NAME: &str = "TuCarro";
+pub const BASE_URL: &str = "https://vehiculos.tucarro.com.co";
+
+const SITE: Site = Site {
+    name: NAME,
+    item_base: "https://articulo.tucarro.com.co",
+};
+
+pub struct TuCarro;
+
+impl Source for TuCarro {
+    fn id(&self) -> &'static str {
+        ID
+    }
+
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn base_url(&self) -> &str {
+        BASE_URL
+    }
+
+    fn search_url(&self, query: &str, page: usize) -> String {
+        polycard::search_url(BASE_URL, query, page)
+    }
+
+    fn parse_page(&self, body: &str, max_results: usize) -> ParsedPage {
+        polycard::parse_page(body, &SITE, max_results)
+    }
+
+    /// Results, item and tracker pages live on different subdomains.
+    fn handles(&self, url: &str) -> bool {
+        polycard::on_domain(url, "tucarro.com.co")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health;
+    use crate::listing::Extraction;
+    use crate::mileage::DistanceUnit;
+    use crate::price::Currency;
+    use crate::seller::SellerType;
+
+    /// The saved page trimmed to three cards.
+    const TRIMMED_RESULTS: &str = include_str!("../../fixtures/tucarro_results_trimmed.html");
+
+    /// The results page saved at the repository root.
+    fn saved_page() -> String {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../tucarrosource.html");
+        std::fs::read_to_string(path).expect("tucarrosource.html is readable")
+    }
+
+    #[test]
+    fn test_search_url() {
+        assert_eq!(
+            TuCarro.search_url("Toyota Corolla", 1),
+            "https://vehiculos.tucarro.com.co/toyota-corolla_NoIndex_True"
+        );
+        assert_eq!(
+            TuCarro.search_url("Mazda CX-5", 3),
+            "https://vehiculos.tucarro.com.co/mazda-cx-5_Desde_97_NoIndex_True"
+        );
+    }
+
+    #[test]
+    fn test_handles() {
+        assert!(TuCarro.handles("https://articulo.tucarro.com.co/MCO-1-_JM"));
+        assert!(TuCarro.handles("https://carros.tucarro.com.co/mazda"));
+        assert!(!TuCarro.handles("https://www.vendetunave.co/vehiculo/1"));
+        assert!(!TuCarro.handles("https://nottucarro.com.co/x"));
+    }
+
+    #[test]
+    fn test_parse_saved_page() {
+        let page = TuCarro.parse_page(&saved_page(), usize::MAX);
+        assert_eq!(page.matched.as_deref(), Some(health::PRELOADED_STATE_MATCH));
+        assert_eq!(page.listings.len(), 51);
+        assert_eq!(
+            page.next_page.as_deref(),
+            Some("https://vehiculos.tucarro.com.co/_Desde_49_NoIndex_True")
+        );
+
+        let onix = page
+            .listings
+            .iter()
+            .find(|l| l.id.as_deref() == Some("MCO3558000760"))
+            .expect("the Onix card is parsed");
+        assert_eq!(onix.title, "Chevrolet Onix 1.0 Turbo Rs Mt. 4x2");
+        assert_eq!(
+            onix.url,
+            "https://articulo.tucarro.com.co/MCO-3558000760-chevrolet-onix-10-turbo-rs-2023-_JM"
+        );
+        assert_eq!(onix.price.amount, Some(65_900_000));
+        assert_eq!(onix.year, Some(2023));
+        assert_eq!(onix.mileage, Some(16_500));
+        assert_eq!(onix.mileage_unit, Some(DistanceUnit::Km));
+        assert_eq!(onix.city.as_deref(), Some("Medellín - Antioquia"));
+        assert_eq!(
+            onix.image_url.as_deref(),
+            Some("https://http2.mlstatic.com/D_Q_NP_2X_698812-MCO107024033443_022026-E.webp")
+        );
+        assert_eq!(onix.seller_type, None);
+        assert_eq!(onix.source, "TuCarro");
+        assert_eq!(onix.extraction, Extraction::PreloadedState);
+
+        // Promoted cards link through a click tracker; their URL is rebuilt
+        // from the item id, and the official-store badge marks a dealer.
+        let promoted = page
+            .listings
+            .iter()
+            .find(|l| l.id.as_deref() == Some("MCO1727467385"))
+            .expect("promoted cards are kept");
+        assert_eq!(
+            promoted.url,
+            "https://articulo.tucarro.com.co/MCO-1727467385-_JM"
+        );
+        assert_eq!(promoted.seller_type, Some(SellerType::Dealer));
+        assert!(page.listings.iter().all(|l| !l.url.contains('#')));
+    }
+
+    #[test]
+    fn test_parse_saved_page_html_fallback() {
+        // Without the preloaded state the rendered cards are read instead.
+        let body = saved_page().replace("__NORDIC_RENDERING_CTX__", "ctx-removed");
+        let page = TuCarro.parse_page(&body, usize::MAX);
+        assert_eq!(page.matched.as_deref(), Some(polycard::CARD_SELECTOR));
+        assert_eq!(page.listings.len(), 51);
+
+        let onix = page
+            .listings
+            .iter()
+            .find(|l| l.id.as_deref() == Some("MCO3558000760"))
+            .expect("the Onix card is parsed");
+        assert_eq!(onix.title, "Chevrolet Onix 1.0 Turbo Rs Mt. 4x2");
+        assert_eq!(onix.price.amount, Some(65_900_000));
+        assert_eq!(onix.year, Some(2023));
+        assert_eq!(onix.mileage, Some(16_500));
+        assert_eq!(onix.city.as_deref(), Some("Medellín - Antioquia"));
+        assert_eq!(onix.extraction, Extraction::HtmlCard);
+        assert_eq!(
+            page.listings
+                .iter()
+                .filter(|l| l.seller_type == Some(SellerType::Dealer))
+                .count(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_parse_trimmed_page() {
+        let page = TuCarro.parse_page(TRIMMED_RESULTS, usize::MAX);
+        assert_eq!(page.matched.as_deref(), Some(health::PRELOADED_STATE_MATCH));
+        assert_eq!(
+            page.next_page.as_deref(),
+            Some("https://vehiculos.tucarro.com.co/_Desde_49_NoIndex_True")
+        );
+        let listings = &page.listings;
+        assert_eq!(listings.len(), 3);
+
+        let promoted = &listings[0];
+        assert_eq!(promoted.id.as_deref(), Some("MCO1727467385"));
+        assert_eq!(
+            promoted.url,
+            "https://articulo.tucarro.com.co/MCO-1727467385-_JM"
+        );
+        assert_eq!(promoted.seller_type, Some(SellerType::Dealer));
+
+        let onix = &listings[1];
+        assert_eq!(onix.price.amount, Some(65_900_000));
+        assert_eq!(onix.price.currency, Currency::Cop);
+
+        let audi = &listings[2];
+        assert_eq!(audi.title, "Audi Q8 S Line");
+        assert_eq!(
+            audi.url,
+            "https://articulo.tucarro.com.co/MCO-2816465162-audi-q8-s-line-_JM"
+        );
+        assert_eq!(audi.price.amount, Some(409_900_000));
+        assert_eq!(audi.mileage, Some(0));
+        assert_eq!(audi.seller_type, Some(SellerType::Dealer));
+        assert_eq!(audi.source, "TuCarro");
+    }
+
+    #[test]
+    fn test_parse_trimmed_page_html_cards() {
+        let body = TRIMMED_RESULTS.replace("__NORDIC_RENDERING_CTX__", "ctx-removed");
+        let page = TuCarro.parse_page(&body, usize::MAX);
+        assert_eq!(page.matched.as_deref(), Some(polycard::CARD_SELECTOR));
+        let listings = &page.listings;
+        assert_eq!(listings.len(), 3);
+        assert_eq!(listings[0].seller_type, Some(SellerType::Dealer));
+        assert_eq!(listings[1].id.as_deref(), Some("MCO3558000760"));
+        assert_eq!(listings[1].price.amount, Some(65_900_000));
+        assert_eq!(listings[1].year, Some(2023));
+        assert_eq!(listings[1].mileage, Some(16_500));
+        assert_eq!(listings[1].city.as_deref(), Some("Medellín - Antioquia"));
+        assert_eq!(listings[2].seller_type, Some(SellerType::Dealer));
+        assert_eq!(listings[1].extraction, Extraction::HtmlCard);
+    }
+}
@@ -0,0 +1,473 @@
+//! vendetunave.co, the original source: `__NEXT_DATA__` first, then the
+//! extraction profile's HTML cards, with schema.org JSON-LD filling gaps.
+
+use regex::Regex;
+use reqwest::blocking::Client;
+use scraper::{Html, Selector};
+
+use super::{ParsedPage, Source};
+use crate::detail::{self, DetailRecord};
+use crate::health;
+use crate::images;
+use crate::listing::{Extraction, Listing};
+use crate::mileage;
+use crate::next_data;
+use crate::price::Price;
+use crate::profile::{FieldRule, Profile};
+use crate::published;
+use crate::seller;
+use crate::sources;
+use crate::structured::{self, StructuredItem};
+use crate::terms;
+use crate::url_encode;
+use crate::year;
+
+pub const ID: &str = "vendetunave";
+pub const NAME: &str = "VendeTuNave";
+
+pub struct VendeTuNave {
+    /// Card selectors for the HTML fallback, from `--profile` or embedded.
+    profile: Profile,
+}
+
+impl VendeTuNave {
+    pub fn new(profile: Profile) -> Self {
+        VendeTuNave { profile }
+    }
+}
+
+impl Source for VendeTuNave {
+    fn id(&self) -> &'static str {
+        ID
+    }
+
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn base_url(&self) -> &str {
+        &self.profile.base_url
+    }
+
+    fn search_url(&self, query: &str, page: usize) -> String {
+        search_url(&self.profile.base_url, query, page)
+    }
+
+    fn parse_page(&self, body: &str, max_results: usize) -> ParsedPage {
+        let document = Html::parse_document(body);
+        let mut page = parse_listings(&document, &self.profile, max_results);
+        page.next_page = next_page_link(&document, &self.profile.base_url);
+        page
+    }
+
+    fn fetch(
+        &self,
+        client: &Client,
+        url: &str,
+    ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        sources::fetch_html(client, url, "vendetunave.co")
+    }
+
+    fn resolve_link(&self, href: &str) -> Option<String> {
+        self.profile.absolute_url(href)
+    }
+
+    fn detail_url(&self, target: &str) -> Option<String> {
+        Some(detail::detail_url(&self.profile.base_url, target))
+    }
+
+    fn parse_detail(&self, body: &str, url: &str) -> Option<DetailRecord> {
+        detail::parse_detail(body, url, &self.profile.base_url)
+    }
+}
+
+/// Search URL for one results page of the site at `base_url`, the
+/// profile's.
+pub fn search_url(base_url: &str, query: &str, page: usize) -> String {
+    // vendetunave.co accepts the search term via the `search` query parameter on
+    // the carros y camionetas category page, and the page number via `page`.
+    let encoded_query = url_encode(query);
+    let url = format!("{base_url}/vehiculos/carrosycamionetas?search={encoded_query}");
+    if page > 1 {
+        format!("{url}&page={page}")
+    } else {
+        url
+    }
+}
+
+/// The href of a `rel="next"` pagination link, if the page has one,
+/// resolved against `base_url`.
+pub fn next_page_link(document: &Html, base_url: &str) -> Option<String> {
+    let sel = Selector::parse("a[rel~='next'][href], link[rel~='next'][href]").ok()?;
+    let href = document.select(&sel).next()?.value().attr("href")?;
+    sources::resolve_link(base_url, href)
+}
+
+/// Parse vehicle listings from a results page.
+///
+/// The `__NEXT_DATA__` JSON payload is the primary source; the HTML card
+/// heuristics are only used when it is missing or holds no vehicles.
+/// schema.org JSON-LD items fill gaps in the former and override the
+/// latter, and stand in for both when neither yields anything.
+pub fn parse_listings(document: &Html, profile: &Profile, max_results: usize) -> ParsedPage {
+    let structured = structured::json_ld_items(document);
+
+    if let Some(vehicles) = next_data::extract_vehicles(document) {
+        let mut listings: Vec<Listing> = vehicles
+            .into_iter()
+            .filter_map(|v| v.into_listing())
+            .take(max_results)
+            .collect();
+        if !listings.is_empty() {
+            merge_structured(&mut listings, &structured, profile, false);
+            return ParsedPage {
+                listings,
+                matched: Some(health::NEXT_DATA_MATCH.to_string()),
+                ..ParsedPage::empty()
+            };
+        }
+    }
+
+    let mut page = parse_html_cards(document, profile, max_results);
+    if page.listings.is_empty() {
+        page.listings = structured
+            .iter()
+            .filter_map(|item| {
+                let mut listing = structured::into_listing(item, NAME, Extraction::JsonLd)?;
+                listing.url = profile.absolute_url(&listing.url).unwrap_or_default();
+                listing.id = listing.id.or_else(|| extract_vehicle_id(&listing.url));
+                Some(listing)
+            })
+            .take(max_results)
+            .collect();
+        if !page.listings.is_empty() {
+            page.matched = Some(health::JSON_LD_MATCH.to_string());
+        }
+    } else {
+        merge_structured(&mut page.listings, &structured, profile, true);
+    }
+    if page.matched.is_none() && next_data::has_vehicle_list(document) {
+        page.matched = Some(health::NEXT_DATA_MATCH.to_string());
+    }
+    page
+}
+
+/// Merge each JSON-LD item into the listing with the same URL.
+fn merge_structured(
+    listings: &mut [Listing],
+    items: &[StructuredItem],
+    profile: &Profile,
+    overwrite: bool,
+) {
+    for item in items {
+        let Some(url) = item.url.as_deref().and_then(|u| profile.absolute_url(u)) else {
+            continue;
+        };
+        let url = url.trim_end_matches('/');
+        if let Some(listing) = listings
+            .iter_mut()
+            .find(|l| !l.url.is_empty() && l.url.trim_end_matches('/') == url)
+        {
+            // The listing's URL is already absolute; keep it.
+            let item = StructuredItem {
+                url: None,
+                ..item.clone()
+            };
+            structured::apply(listing, &item, Extraction::JsonLd, overwrite);
+        }
+    }
+}
+
+/// Fallback: read listings from the rendered HTML cards using the selectors
+/// of the extraction profile.
+fn parse_html_cards(document: &Html, profile: &Profile, max_results: usize) -> ParsedPage {
+    let mut page = ParsedPage::empty();
+
+    let Some((matched, card_selector)) = profile.match_cards(document) else {
+        return page;
+    };
+    page.matched = Some(matched.to_string());
+
+    for card in document.select(card_selector) {
+        if page.listings.len() >= max_results {
+            break;
+        }
+        let field = |rule: &Option<FieldRule>| rule.as_ref().and_then(|r| r.extract(&card));
+
+        let Some(title) = profile.title.extract(&card) else {
+            page.skipped_empty_title += 1;
+            continue;
+        };
+
+        let price = field(&profile.price).map_or_else(Price::missing, |p| Price::parse(&p));
+        let model_year = year::extract(&title, field(&profile.year).as_deref());
+        let mileage = field(&profile.mileage).and_then(|t| mileage::extract(&t));
+        let city = field(&profile.city);
+        let url = field(&profile.url)
+            .and_then(|href| profile.absolute_url(&href))
+            .unwrap_or_default();
+
+        let mut listing = Listing::new(title, url, NAME, Extraction::HtmlCard);
+        listing.id = extract_vehicle_id(&listing.url);
+        listing.price = price;
+        listing.year = model_year.map(|y| y.year);
+        listing.year_confidence = model_year.map(|y| y.confidence);
+        listing.mileage = mileage.map(|m| m.km);
+        listing.mileage_unit = mileage.map(|m| m.unit);
+        listing.city = city;
+        listing.images = images::collect(&card, |src| profile.absolute_url(src));
+        listing.image_url = field(&profile.image)
+            .filter(|src| !images::is_placeholder(src))
+            .and_then(|src| profile.absolute_url(&src))
+            .or_else(|| listing.images.first().cloned());
+        if let Some(cover) = &listing.image_url
+            && !listing.images.contains(cover)
+        {
+            listing.images.insert(0, cover.clone());
+        }
+        let card_text = card.text().collect::<Vec<_>>().join(" ");
+        listing.published_raw = field(&profile.published)
+            .or_else(|| published::find_phrase(&card_text).map(str::to_string));
+        listing.seller_type = field(&profile.seller)
+            .and_then(|badge| seller::classify(&badge))
+            .or_else(|| field(&profile.seller_name).and_then(|name| seller::classify(&name)));
+        terms::from_text(&card_text).fill(&mut listing);
+        listing.record_provenance(Extraction::HtmlCard);
+        page.listings.push(listing);
+    }
+
+    page
+}
+
+/// Extract the site's vehicle id from a `/vehiculo/<id>/<slug>` URL.
+pub fn extract_vehicle_id(url: &str) -> Option<String> {
+    let re = Regex::new(r"/vehiculo/(\d+)").ok()?;
+    Some(re.captures(url)?[1].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_vehicle_id() {
+        assert_eq!(
+            extract_vehicle_id("https://www.vendetunave.co/vehiculo/4521/mazda-3").as_deref(),
+            Some("4521")
+        );
+        assert_eq!(
+            extract_vehicle_id("https://www.vendetunave.co/vehiculos/mazda"),
+            None
+        );
+    }
+
+    const BASE_URL: &str = "https://www.vendetunave.co";
+
+    #[test]
+    fn test_search_url() {
+        assert_eq!(
+            search_url(BASE_URL, "Mazda 3", 1),
+            "https://www.vendetunave.co/vehiculos/carrosycamionetas?search=Mazda+3"
+        );
+        assert_eq!(
+            search_url(BASE_URL, "Mazda 3", 2),
+            "https://www.vendetunave.co/vehiculos/carrosycamionetas?search=Mazda+3&page=2"
+        );
+    }
+
+    #[test]
+    fn test_urls_follow_the_profile_base_url() {
+        let profile = Profile::from_toml(&include_str!("../../profiles/vendetunave.toml").replace(
+            "https://www.vendetunave.co",
+            "https://staging.vendetunave.co/",
+        ))
+        .unwrap();
+        let source = VendeTuNave::new(profile);
+        assert_eq!(
+            source.search_url("Kia", 2),
+            "https://staging.vendetunave.co/vehiculos/carrosycamionetas?search=Kia&page=2"
+        );
+        let page = source.parse_page(
+            r#"<html><a rel="next" href="/vehiculos?page=3">»</a></html>"#,
+            10,
+        );
+        assert_eq!(
+            page.next_page.as_deref(),
+            Some("https://staging.vendetunave.co/vehiculos?page=3")
+        );
+    }
+
+    #[test]
+    fn test_next_page_link() {
+        let document = Html::parse_document(
+            r#"<html><body><a rel="next" href="/vehiculos/carrosycamionetas?page=3">Siguiente</a></body></html>"#,
+        );
+        assert_eq!(
+            next_page_link(&document, BASE_URL).as_deref(),
+            Some("https://www.vendetunave.co/vehiculos/carrosycamionetas?page=3")
+        );
+        assert_eq!(
+            next_page_link(&Html::parse_document("<html></html>"), BASE_URL),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_listings_empty_html() {
+        let page = parse_listings(
+            &Html::parse_document("<html><body></body></html>"),
+            &Profile::embedded(),
+            20,
+        );
+        assert!(page.listings.is_empty());
+        assert_eq!(page.matched, None);
+    }
+
+    #[test]
+    fn test_parse_listings_zero_results() {
+        let html = r#"
+            <html><body>
+                <script id="__NEXT_DATA__" type="application/json">
+                    {"props":{"pageProps":{"data":{"vehicles":[],"total":0}}}}
+                </script>
+            </body></html>
+        "#;
+        let page = parse_listings(&Html::parse_document(html), &Profile::embedded(), 20);
+        assert!(page.listings.is_empty());
+        assert_eq!(page.matched.as_deref(), Some("__NEXT_DATA__"));
+
+        // The payload is there but the results moved: still unrecognized.
+        let html = r#"
+            <html><body>
+                <script id="__NEXT_DATA__" type="application/json">
+                    {"props":{"pageProps":{"search":{"items":[]}}}}
+                </script>
+            </body></html>
+        "#;
+        let page = parse_listings(&Html::parse_document(html), &Profile::embedded(), 20);
+        assert_eq!(page.matched, None);
+    }
+
+    #[test]
+    fn test_parse_listings_with_article() {
+        let html = r#"
+            <html><body>
+                <article>
+                    <h2>Toyota Corolla 2020</h2>
+                    <span class="price">$45.000.000</span>
+                    <span class="city">Medellín</span>
+                    <span class="badge">Concesionario</span>
+                    <small>Publicado hace 2 días</small>
+                    <a href="/vehiculos/toyota-corolla-2020">Ver más</a>
+                    <p>35.000 km recorridos</p>
+                </article>
+                <article><span class="price">$1</span></article>
+            </body></html>
+        "#;
+        let page = parse_listings(&Html::parse_document(html), &Profile::embedded(), 20);
+        assert_eq!(page.matched.as_deref(), Some("article"));
+        assert_eq!(page.skipped_empty_title, 1);
+        let listings = &page.listings;
+        assert_eq!(listings.len(), 1);
+        assert_eq!(listings[0].title, "Toyota Corolla 2020");
+        assert_eq!(listings[0].year, Some(2020));
+        assert_eq!(listings[0].mileage, Some(35000));
+        assert_eq!(listings[0].price.amount, Some(45_000_000));
+        assert_eq!(listings[0].seller_type, Some(seller::SellerType::Dealer));
+        assert_eq!(
+            listings[0].published_raw.as_deref(),
+            Some("Publicado hace 2 días")
+        );
+        assert_eq!(listings[0].source, "VendeTuNave");
+        assert_eq!(listings[0].extraction, Extraction::HtmlCard);
+    }
+
+    #[test]
+    fn test_placeholder_is_not_the_cover() {
+        let html = r#"
+            <html><body>
+                <article>
+                    <h2>Renault Sandero 2017</h2>
+                    <img src="/static/placeholder.gif" data-src="/images/sandero.jpg">
+                    <img src="data:image/gif;base64,R0lGOD" data-src="/images/sandero-2.jpg">
+                </article>
+            </body></html>
+        "#;
+        let page = parse_listings(&Html::parse_document(html), &Profile::embedded(), 20);
+        let listing = &page.listings[0];
+        assert_eq!(
+            listing.image_url.as_deref(),
+            Some("https://www.vendetunave.co/images/sandero.jpg")
+        );
+        assert_eq!(
+            listing.images,
+            vec![
+                "https://www.vendetunave.co/images/sandero.jpg",
+                "https://www.vendetunave.co/images/sandero-2.jpg",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_seller_read_from_seller_elements_only() {
+        let html = r#"
+            <html><body>
+                <article>
+                    <h2>Mazda 3 2019</h2>
+                    <p>Como de agencia, recibo autos de menor valor</p>
+                </article>
+                <article>
+                    <h2>Kia Rio 2020</h2>
+                    <div class="seller-name">Autos La 80 S.A.S.</div>
+                </article>
+            </body></html>
+        "#;
+        let page = parse_listings(&Html::parse_document(html), &Profile::embedded(), 20);
+        assert_eq!(page.listings[0].seller_type, None);
+        assert_eq!(
+            page.listings[1].seller_type,
+            Some(seller::SellerType::Dealer)
+        );
+    }
+
+    #[test]
+    fn test_parse_listings_prefers_next_data() {
+        let html = r#"
+            <html><body>
+                <article><h2>Card title</h2></article>
+                <script id="__NEXT_DATA__" type="application/json">
+                    {"props":{"pageProps":{"data":{"vehicles":[{"id":7,"title":"Kia Picanto","tipoVendedor":"Particular"}]}}}}
+                </script>
+            </body></html>
+        "#;
+        let page = parse_listings(&Html::parse_document(html), &Profile::embedded(), 20);
+        assert_eq!(page.matched.as_deref(), Some("__NEXT_DATA__"));
+        let listings = &page.listings;
+        assert_eq!(listings.len(), 1);
+        assert_eq!(listings[0].title, "Kia Picanto");
+        assert_eq!(listings[0].seller_type, Some(seller::SellerType::Private));
+        assert_eq!(listings[0].extraction, Extraction::NextData);
+    }
+
+    #[test]
+    fn test_parse_listings_json_ld_only() {
+        let html = r#"
+            <html><head><script type="application/ld+json">
+                {"@type":"ItemList","itemListElement":[
+                    {"@type":"ListItem","item":{"@type":"Car","name":"Chevrolet Onix",
+                     "url":"/vehiculo/31/chevrolet-onix","vehicleModelDate":"2022"}}]}
+            </script></head><body></body></html>
+        "#;
+        let page = parse_listings(&Html::parse_document(html), &Profile::embedded(), 20);
+        assert_eq!(page.matched.as_deref(), Some("application/ld+json"));
+        let listing = &page.listings[0];
+        assert_eq!(
+            listing.url,
+            "https://www.vendetunave.co/vehiculo/31/chevrolet-onix"
+        );
+        assert_eq!(listing.id.as_deref(), Some("31"));
+        assert_eq!(listing.year, Some(2022));
+        assert_eq!(listing.extraction, Extraction::JsonLd);
+        assert_eq!(listing.provenance["title"], Extraction::JsonLd);
+    }
+}
@@ -0,0 +1,429 @@
+//! schema.org JSON-LD and OpenGraph extraction.
+//!
+//! Many marketplaces embed `application/ld+json` `Vehicle`, `Car` or `Offer`
+//! objects and `og:` meta tags.  These are read into `StructuredItem`s and
+//! merged into listings, taking priority over text scraped from the cards.
+
+use scraper::{Html, Selector};
+use serde_json::Value;
+
+use crate::listing::{Extraction, Listing};
+use crate::mileage::{self, DistanceUnit, KM_PER_MILE, Mileage};
+use crate::price::Price;
+use crate::year;
+
+/// schema.org types treated as a vehicle listing.
+const LISTING_TYPES: &[&str] = &[
+    "Vehicle",
+    "Car",
+    "Motorcycle",
+    "MotorizedBicycle",
+    "BusOrCoach",
+    "Product",
+    "IndividualProduct",
+];
+
+/// One listing's worth of structured data.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StructuredItem {
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub url: Option<String>,
+    pub price: Option<Price>,
+    pub year: Option<u32>,
+    pub mileage: Option<Mileage>,
+    pub city: Option<String>,
+    pub brand: Option<String>,
+    pub model: Option<String>,
+    pub fuel: Option<String>,
+    pub transmission: Option<String>,
+    pub condition: Option<String>,
+    pub description: Option<String>,
+    pub images: Vec<String>,
+    /// `datePosted` / `datePublished`, as written.
+    pub published: Option<String>,
+}
+
+/// Every listing-like JSON-LD object on the page, in document order.
+pub fn json_ld_items(document: &Html) -> Vec<StructuredItem> {
+    let Ok(sel) = Selector::parse("script[type='application/ld+json']") else {
+        return Vec::new();
+    };
+    let mut items = Vec::new();
+    for script in document.select(&sel) {
+        let raw = script.text().collect::<String>();
+        if let Ok(value) = serde_json::from_str::<Value>(raw.trim()) {
+            collect_items(&value, &mut items);
+        }
+    }
+    items
+}
+
+/// The page-level OpenGraph item, if the page has any `og:` tags.
+pub fn open_graph(document: &Html) -> Option<StructuredItem> {
+    let meta = |names: &[&str]| {
+        names.iter().find_map(|name| {
+            let sel = Selector::parse(&format!(
+                "meta[property='{name}'][content], meta[name='{name}'][content]"
+            ))
+            .ok()?;
+            let content = document.select(&sel).next()?.value().attr("content")?;
+            Some(content.trim().to_string()).filter(|c| !c.is_empty())
+        })
+    };
+
+    let amount = meta(&["product:price:amount", "og:price:amount"]);
+    let currency = meta(&["product:price:currency", "og:price:currency"]).unwrap_or_default();
+    let item = StructuredItem {
+        title: meta(&["og:title"]),
+        url: meta(&["og:url"]),
+        description: meta(&["og:description"]),
+        images: meta(&["og:image:secure_url", "og:image"])
+            .into_iter()
+            .collect(),
+        price: amount.map(|a| Price::parse(&format!("{a} {currency}"))),
+        ..Default::default()
+    };
+    (item != StructuredItem::default()).then_some(item)
+}
+
+fn collect_items(value: &Value, out: &mut Vec<StructuredItem>) {
+    match value {
+        Value::Array(values) => values.iter().for_each(|v| collect_items(v, out)),
+        Value::Object(obj) => {
+            if let Some(graph) = obj.get("@graph") {
+                collect_items(graph, out);
+            }
+            if let Some(list) = obj.get("itemListElement") {
+                collect_items(list, out);
+            }
+            if has_type(value, &["ListItem"])
+                && let Some(item) = obj.get("item")
+            {
+                collect_items(item, out);
+            }
+            if has_type(value, LISTING_TYPES) {
+                out.push(item_from_json(value, None));
+            } else if has_type(value, &["Offer"])
+                && let Some(offered) = obj.get("itemOffered")
+                && has_type(offered, LISTING_TYPES)
+            {
+                out.push(item_from_json(offered, Some(value)));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn has_type(value: &Value, types: &[&str]) -> bool {
+    match value.get("@type") {
+        Some(Value::String(t)) => types.contains(&t.as_str()),
+        Some(Value::Array(ts)) => ts
+            .iter()
+            .any(|t| t.as_str().is_some_and(|t| types.contains(&t))),
+        _ => false,
+    }
+}
+
+/// Build an item from a vehicle object.  `offer` is the enclosing `Offer`
+/// when the vehicle was found as its `itemOffered`.
+fn item_from_json(v: &Value, offer: Option<&Value>) -> StructuredItem {
+    let offer = offer.or_else(|| match v.get("offers") {
+        Some(Value::Array(offers)) => offers.first(),
+        other => other,
+    });
+
+    StructuredItem {
+        id: text(v, &["sku", "productID", "vehicleIdentificationNumber"]),
+        title: text(v, &["name"]),
+        url: text(v, &["url"]).or_else(|| offer.and_then(|o| text(o, &["url"]))),
+        price: offer.and_then(price_from_offer),
+        year: text(
+            v,
+            &[
+                "vehicleModelDate",
+                "modelDate",
+                "productionDate",
+                "dateVehicleFirstRegistered",
+            ],
+        )
+        .and_then(|d| d.get(..4)?.parse().ok()),
+        mileage: v.get("mileageFromOdometer").and_then(odometer),
+        city: offer
+            .and_then(|o| o.pointer("/availableAtOrFrom/address/addressLocality"))
+            .or_else(|| v.pointer("/address/addressLocality"))
+            .and_then(Value::as_str)
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty()),
+        brand: text(v, &["brand", "manufacturer"]),
+        model: text(v, &["model"]),
+        fuel: text(v, &["fuelType"]),
+        transmission: text(v, &["vehicleTransmission"]),
+        condition: text(v, &["itemCondition"])
+            .or_else(|| offer.and_then(|o| text(o, &["itemCondition"])))
+            .map(|c| condition_label(&c)),
+        description: text(v, &["description"]),
+        images: images(v.get("image")),
+        published: text(v, &["datePosted", "datePublished"])
+            .or_else(|| offer.and_then(|o| text(o, &["validFrom"]))),
+    }
+}
+
+/// First non-empty string among `keys`, looking through `{name: ...}` objects.
+fn text(v: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|k| match v.get(*k)? {
+        Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Object(o) => o
+            .get("name")
+            .and_then(Value::as_str)
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty()),
+        _ => None,
+    })
+}
+
+fn price_from_offer(offer: &Value) -> Option<Price> {
+    let amount = match offer.get("price").or_else(|| offer.get("lowPrice"))? {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        _ => return None,
+    };
+    let currency = offer
+        .get("priceCurrency")
+        .and_then(Value::as_str)
+        .unwrap_or("COP");
+    Some(Price::parse(&format!("{amount} {currency}")))
+}
+
+/// A `QuantitativeValue` reading; `SMI` (statute miles) is converted to km.
+fn odometer(odometer: &Value) -> Option<Mileage> {
+    let value = match odometer.get("value").unwrap_or(odometer) {
+        Value::Number(n) => n.as_f64()?,
+        Value::String(s) => mileage::parse_number(s.trim())?,
+        _ => return None,
+    };
+    let (km, unit) = match odometer.get("unitCode").and_then(Value::as_str) {
+        Some("SMI") => (value * KM_PER_MILE, DistanceUnit::Miles),
+        _ => (value, DistanceUnit::Km),
+    };
+    (km >= 0.0 && km < f64::from(u32::MAX)).then(|| Mileage {
+        km: km.round() as u32,
+        unit,
+    })
+}
+
+fn condition_label(condition: &str) -> String {
+    match condition.rsplit('/').next().unwrap_or(condition) {
+        "NewCondition" => "Nuevo".to_string(),
+        "UsedCondition" => "Usado".to_string(),
+        "RefurbishedCondition" => "Reacondicionado".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn images(image: Option<&Value>) -> Vec<String> {
+    match image {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Array(values)) => values.iter().flat_map(|v| images(Some(v))).collect(),
+        Some(obj @ Value::Object(_)) => text(obj, &["url", "contentUrl"]).into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Merge `item` into `listing`.  With `overwrite`, structured values replace
+/// what is already there (use for text-scraped listings); without it they
+/// only fill gaps.  Every field written is attributed to `source`.
+pub fn apply(listing: &mut Listing, item: &StructuredItem, source: Extraction, overwrite: bool) {
+    macro_rules! merge {
+        ($field:ident, $value:expr, $is_set:expr) => {
+            if let Some(value) = $value {
+                if overwrite || !$is_set {
+                    listing.$field = value;
+                    listing
+                        .provenance
+                        .insert(stringify!($field).to_string(), source);
+                }
+            }
+        };
+    }
+
+    merge!(id, item.id.clone().map(Some), listing.id.is_some());
+    merge!(title, item.title.clone(), !listing.title.is_empty());
+    merge!(url, item.url.clone(), !listing.url.is_empty());
+    merge!(
+        price,
+        item.price.clone().filter(|p| p.amount.is_some()),
+        listing.price.amount.is_some()
+    );
+    if let Some(year) = item.year
+        && (overwrite || listing.year.is_none())
+    {
+        listing.year = Some(year);
+        listing.year_confidence = Some(year::STRUCTURED_CONFIDENCE);
+        listing.provenance.insert("year".to_string(), source);
+    }
+    if let Some(mileage) = item.mileage
+        && (overwrite || listing.mileage.is_none())
+    {
+        listing.mileage = Some(mileage.km);
+        listing.mileage_unit = Some(mileage.unit);
+        listing.provenance.insert("mileage".to_string(), source);
+    }
+    merge!(city, item.city.clone().map(Some), listing.city.is_some());
+    merge!(brand, item.brand.clone().map(Some), listing.brand.is_some());
+    merge!(model, item.model.clone().map(Some), listing.model.is_some());
+    merge!(fuel, item.fuel.clone().map(Some), listing.fuel.is_some());
+    merge!(
+        transmission,
+        item.transmission.clone().map(Some),
+        listing.transmission.is_some()
+    );
+    merge!(
+        condition,
+        item.condition.clone().map(Some),
+        listing.condition.is_some()
+    );
+    merge!(
+        description,
+        item.description.clone().map(Some),
+        listing.description.is_some()
+    );
+    merge!(
+        published_raw,
+        item.published.clone().map(Some),
+        listing.published_raw.is_some()
+    );
+    merge!(
+        image_url,
+        item.images.first().cloned().map(Some),
+        listing.image_url.is_some()
+    );
+    for image in &item.images {
+        if !listing.images.contains(image) {
+            listing.images.push(image.clone());
+        }
+    }
+}
+
+/// A listing built from structured data alone, when neither the JSON payload
+/// nor the HTML cards produced anything.  Items without a name are skipped.
+pub fn into_listing(
+    item: &StructuredItem,
+    source_name: &str,
+    source: Extraction,
+) -> Option<Listing> {
+    let title = item.title.clone()?;
+    let mut listing = Listing::new(
+        title,
+        item.url.clone().unwrap_or_default(),
+        source_name,
+        source,
+    );
+    apply(&mut listing, item, source, false);
+    listing.record_provenance(source);
+    Some(listing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price::Currency;
+
+    const PAGE: &str = r#"<html><head>
+        <meta property="og:title" content="Mazda CX-5 Grand Touring">
+        <meta property="og:image" content="https://cdn.example/og.jpg">
+        <meta property="product:price:amount" content="98000000">
+        <meta property="product:price:currency" content="COP">
+        <script type="application/ld+json">
+        {"@context":"https://schema.org","@graph":[
+          {"@type":"BreadcrumbList"},
+          {"@type":"ItemList","itemListElement":[
+            {"@type":"ListItem","position":1,"item":{
+              "@type":"Car","name":"Ford Explorer Limited","url":"https://x.co/v/1",
+              "brand":{"@type":"Brand","name":"Ford"},"model":"Explorer",
+              "vehicleModelDate":"2018","fuelType":"Gasolina",
+              "mileageFromOdometer":{"@type":"QuantitativeValue","value":30000,"unitCode":"SMI"},
+              "itemCondition":"https://schema.org/UsedCondition",
+              "image":["https://x.co/1.jpg",{"@type":"ImageObject","url":"https://x.co/2.jpg"}],
+              "offers":{"@type":"Offer","price":"125000000","priceCurrency":"COP",
+                "availableAtOrFrom":{"address":{"addressLocality":"Bogotá"}}}}}
+          ]},
+          {"@type":"Offer","price":18500,"priceCurrency":"USD",
+           "itemOffered":{"@type":"Vehicle","name":"Jeep Wrangler"}}
+        ]}
+        </script>
+    </head><body></body></html>"#;
+
+    #[test]
+    fn test_json_ld_items() {
+        let items = json_ld_items(&Html::parse_document(PAGE));
+        assert_eq!(items.len(), 2);
+
+        let ford = &items[0];
+        assert_eq!(ford.title.as_deref(), Some("Ford Explorer Limited"));
+        assert_eq!(ford.brand.as_deref(), Some("Ford"));
+        assert_eq!(ford.year, Some(2018));
+        assert_eq!(
+            ford.mileage,
+            Some(Mileage {
+                km: 48_280,
+                unit: DistanceUnit::Miles
+            })
+        );
+        assert_eq!(ford.city.as_deref(), Some("Bogotá"));
+        assert_eq!(ford.condition.as_deref(), Some("Usado"));
+        assert_eq!(ford.price.as_ref().unwrap().amount, Some(125_000_000));
+        assert_eq!(
+            ford.images,
+            vec!["https://x.co/1.jpg", "https://x.co/2.jpg"]
+        );
+
+        let jeep = &items[1];
+        assert_eq!(jeep.title.as_deref(), Some("Jeep Wrangler"));
+        assert_eq!(jeep.price.as_ref().unwrap().currency, Currency::Usd);
+    }
+
+    #[test]
+    fn test_odometer_strings() {
+        let km = |value: Value| odometer(&value).map(|m| m.km);
+        assert_eq!(km(serde_json::json!({"value": "45000.0"})), Some(45_000));
+        assert_eq!(km(serde_json::json!({"value": "45.000"})), Some(45_000));
+        assert_eq!(km(serde_json::json!("120,000")), Some(120_000));
+        assert_eq!(km(serde_json::json!({"value": "n/a"})), None);
+    }
+
+    #[test]
+    fn test_open_graph() {
+        let og = open_graph(&Html::parse_document(PAGE)).unwrap();
+        assert_eq!(og.title.as_deref(), Some("Mazda CX-5 Grand Touring"));
+        assert_eq!(og.price.unwrap().amount, Some(98_000_000));
+        assert_eq!(og.images, vec!["https://cdn.example/og.jpg"]);
+        assert_eq!(open_graph(&Html::parse_document("<html></html>")), None);
+    }
+
+    #[test]
+    fn test_apply_priority_and_provenance() {
+        let item = StructuredItem {
+            year: Some(2018),
+            city: Some("Bogotá".into()),
+            ..Default::default()
+        };
+
+        let mut scraped = Listing::new("X".into(), "u".into(), "VendeTuNave", Extraction::HtmlCard);
+        scraped.year = Some(2024);
+        scraped.record_provenance(Extraction::HtmlCard);
+        apply(&mut scraped, &item, Extraction::JsonLd, true);
+        assert_eq!(scraped.year, Some(2018));
+        assert_eq!(scraped.provenance["year"], Extraction::JsonLd);
+        assert_eq!(scraped.provenance["title"], Extraction::HtmlCard);
+
+        let mut from_json =
+            Listing::new("X".into(), "u".into(), "VendeTuNave", Extraction::NextData);
+        from_json.year = Some(2019);
+        apply(&mut from_json, &item, Extraction::JsonLd, false);
+        assert_eq!(from_json.year, Some(2019));
+        assert_eq!(from_json.city.as_deref(), Some("Bogotá"));
+        assert_eq!(from_json.provenance["city"], Extraction::JsonLd);
+    }
+}
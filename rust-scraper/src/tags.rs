@@ -0,0 +1,162 @@
+//! Rule-based feature tags and document-status flags read from listing
+//! titles and descriptions, backed by the bundled `data/feature_tags.json`.
+//!
+//! Rules are phrase lists matched as whole words on normalized text, so
+//! "Cámara de reversa" and "camara de reversa." both hit `reverse_camera`.
+//! The `armored` tag is the exception: it follows `armor::detect`, so it
+//! always agrees with `Listing::armored`.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::armor;
+use crate::listing::Listing;
+use crate::text::normalize;
+
+/// Feature tag of armored listings.
+pub const ARMORED: &str = "armored";
+
+#[derive(Deserialize)]
+struct RuleFile {
+    rules: Vec<Rule>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Kind {
+    /// Equipment or history; emitted in `Listing::tags` when present.
+    Feature,
+    /// Paperwork; emitted in `Listing::document_status` as true or false.
+    Document,
+}
+
+#[derive(Deserialize)]
+struct Rule {
+    tag: String,
+    kind: Kind,
+    present: Vec<String>,
+    /// Phrases stating the opposite, e.g. "sin blindaje" or "soat vencido".
+    #[serde(default)]
+    absent: Vec<String>,
+}
+
+fn rules() -> &'static [Rule] {
+    static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+    RULES.get_or_init(|| {
+        let file: RuleFile = serde_json::from_str(include_str!("../data/feature_tags.json"))
+            .expect("bundled feature tag rules are valid JSON");
+        file.rules
+            .into_iter()
+            .map(|rule| Rule {
+                present: rule.present.iter().map(|p| fold(p)).collect(),
+                absent: rule.absent.iter().map(|p| fold(p)).collect(),
+                ..rule
+            })
+            .collect()
+    })
+}
+
+/// `normalize` without the dots it keeps, so a phrase ending a sentence
+/// still matches.
+fn fold(text: &str) -> String {
+    normalize(&text.replace('.', " "))
+}
+
+/// Feature tags and document flags found in `text`.
+pub fn tag(text: &str) -> (Vec<String>, BTreeMap<String, bool>) {
+    let padded = format!(" {} ", fold(text));
+    let has = |phrases: &[String]| phrases.iter().any(|p| padded.contains(&format!(" {p} ")));
+
+    let mut tags = Vec::new();
+    if armor::detect(text).is_some_and(|armor| armor.armored) {
+        tags.push(ARMORED.to_string());
+    }
+    let mut documents = BTreeMap::new();
+    for rule in rules() {
+        let stated = if has(&rule.absent) {
+            Some(false)
+        } else if has(&rule.present) {
+            Some(true)
+        } else {
+            None
+        };
+        match (rule.kind, stated) {
+            (Kind::Feature, Some(true)) => tags.push(rule.tag.clone()),
+            (Kind::Document, Some(status)) => {
+                documents.insert(rule.tag.clone(), status);
+            }
+            _ => {}
+        }
+    }
+    (tags, documents)
+}
+
+/// Whether `listing` carries `tag`, either as a feature or as a document
+/// flagged true.
+pub fn has_tag(listing: &Listing, tag: &str) -> bool {
+    listing.tags.iter().any(|t| t == tag) || listing.document_status.get(tag) == Some(&true)
+}
+
+/// Validate a `--require-tag` value against the rule file.
+pub fn parse_tag(arg: &str) -> Result<String, String> {
+    let tag = arg.trim().to_lowercase().replace('-', "_");
+    let known: Vec<_> = std::iter::once(ARMORED)
+        .chain(rules().iter().map(|r| r.tag.as_str()))
+        .collect();
+    if known.contains(&tag.as_str()) {
+        Ok(tag)
+    } else {
+        Err(format!(
+            "unknown tag {arg:?}; available: {}",
+            known.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_features_and_documents() {
+        let (tags, documents) = tag("Toyota Prado TXL 4x4 blindado nivel 3\n\
+             Único dueño, full equipo, techo corredizo, cámara de reversa y 7 airbags. \
+             SOAT vigente hasta marzo, tecnomecánica al día.");
+        assert_eq!(
+            tags,
+            vec![
+                "armored",
+                "four_wheel_drive",
+                "sunroof",
+                "single_owner",
+                "full_equipment",
+                "airbags",
+                "reverse_camera",
+            ]
+        );
+        assert_eq!(documents.get("soat"), Some(&true));
+        assert_eq!(documents.get("tecnomecanica"), Some(&true));
+        assert_eq!(documents.get("taxes"), None);
+    }
+
+    #[test]
+    fn test_negations() {
+        let (tags, documents) = tag("Mazda 3 no blindado, 4x2. SOAT vencido, sin tecnomecánica.");
+        assert!(tags.is_empty());
+        assert!(tag("Fue blindada, hoy desblindada.").0.is_empty());
+        assert_eq!(documents.get("soat"), Some(&false));
+        assert_eq!(documents.get("tecnomecanica"), Some(&false));
+        // Whole words only.
+        assert!(tag("Kia Picanto fullerton").0.is_empty());
+    }
+
+    #[test]
+    fn test_parse_tag() {
+        assert_eq!(parse_tag("Reverse-Camera").as_deref(), Ok("reverse_camera"));
+        assert_eq!(parse_tag("soat").as_deref(), Ok("soat"));
+        assert_eq!(parse_tag("Armored").as_deref(), Ok("armored"));
+        assert!(parse_tag("jacuzzi").unwrap_err().contains("armored"));
+    }
+}
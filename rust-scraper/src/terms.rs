@@ -0,0 +1,324 @@
+//! Sale terms: whether the seller finances, takes a trade-in (permuta) and
+//! whether the price is fixed, negotiable or to be agreed.  The site flags
+//! are preferred; these matchers cover the phrases sellers write instead.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::listing::Listing;
+use crate::price::PriceStatus;
+use crate::text::normalize;
+
+/// How firm the asking price is.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceType {
+    /// "Precio fijo", "no negociable".
+    Fixed,
+    /// "Negociable", "escucho ofertas".
+    Negotiable,
+    /// "A convenir": no real price is given.
+    ToBeAgreed,
+}
+
+/// Checked before `NEGOTIABLE`, which "no negociable" would also hit.
+const FIXED: &[&str] = &[
+    "precio fijo",
+    "no negociable",
+    "innegociable",
+    "no se negocia",
+];
+const NEGOTIABLE: &[&str] = &[
+    "negociable",
+    "precio negociable",
+    "conversable",
+    "charlable",
+    "escucho ofertas",
+    "se escuchan ofertas",
+    "recibo ofertas",
+];
+const TO_BE_AGREED: &[&str] = &[
+    "a convenir",
+    "precio a convenir",
+    "por convenir",
+    "a consultar",
+    "consultar precio",
+];
+
+const NO_FINANCING: &[&str] = &[
+    "no financio",
+    "no se financia",
+    "sin financiacion",
+    "no hay financiacion",
+    "solo contado",
+    "solo de contado",
+    "unicamente de contado",
+];
+const FINANCING: &[&str] = &[
+    "financiacion",
+    "se financia",
+    "financio",
+    "financiamos",
+    "financiamiento",
+    "a credito",
+    "con credito",
+    "credito directo",
+    "facilidades de pago",
+];
+
+const NO_TRADE_IN: &[&str] = &[
+    "no permuto",
+    "no permuta",
+    "no permutas",
+    "sin permuta",
+    "no recibo carro",
+    "no recibo vehiculo",
+    "no recibo vehiculos",
+    "no se recibe carro",
+    "no cambios",
+    "no acepto cambios",
+];
+const TRADE_IN: &[&str] = &[
+    "permuta",
+    "permuto",
+    "permutas",
+    "recibo carro",
+    "recibo vehiculo",
+    "recibo moto",
+    "recibo menor valor",
+    "recibo de menor valor",
+    "recibo carro de menor valor",
+    "se recibe carro",
+    "se recibe vehiculo",
+    "acepto carro",
+    "acepto cambios",
+];
+
+/// Terms stated somewhere; each is `None` when nothing was said about it.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Terms {
+    pub accepts_financing: Option<bool>,
+    pub accepts_trade_in: Option<bool>,
+    pub price_type: Option<PriceType>,
+}
+
+impl Terms {
+    /// Fill the listing's unset terms from `self`.  A price type the price
+    /// itself implies takes precedence, so the two never disagree.
+    pub fn fill(self, listing: &mut Listing) {
+        listing.accepts_financing = listing.accepts_financing.or(self.accepts_financing);
+        listing.accepts_trade_in = listing.accepts_trade_in.or(self.accepts_trade_in);
+        listing.price_type = from_status(listing.price.status)
+            .or(listing.price_type)
+            .or(self.price_type);
+    }
+}
+
+/// The price type a parsed price implies: "Consultar" is to be agreed and a
+/// price marked negotiable is negotiable.
+pub fn from_status(status: PriceStatus) -> Option<PriceType> {
+    match status {
+        PriceStatus::OnRequest => Some(PriceType::ToBeAgreed),
+        PriceStatus::Negotiable => Some(PriceType::Negotiable),
+        PriceStatus::Listed | PriceStatus::Missing => None,
+    }
+}
+
+/// Words that deny a phrase they shortly precede: "no recibo permutas",
+/// "sin financiación", "ni permuta".
+const NEGATIONS: &[&str] = &["no", "sin", "ni"];
+/// How many words back a negation still reaches, enough for "no se hace
+/// permuta".
+const NEGATION_REACH: usize = 3;
+
+/// Matches normalized phrases on word boundaries, clause by clause so a
+/// negation never reaches past a comma or full stop.
+struct Phrases(Vec<String>);
+
+impl Phrases {
+    fn new(text: &str) -> Self {
+        Phrases(
+            text.split(['.', ',', ';', ':', '!', '?', '\n'])
+                .map(normalize)
+                .filter(|clause| !clause.is_empty())
+                .map(|clause| format!(" {clause} "))
+                .collect(),
+        )
+    }
+
+    fn has(&self, phrases: &[&str]) -> bool {
+        self.0
+            .iter()
+            .any(|clause| phrases.iter().any(|p| clause.contains(&format!(" {p} "))))
+    }
+
+    /// Whether a phrase matches with no negation in the words before it.
+    fn affirms(&self, phrases: &[&str]) -> bool {
+        self.0.iter().any(|clause| {
+            phrases.iter().any(|p| {
+                clause.match_indices(&format!(" {p} ")).any(|(at, _)| {
+                    !clause[..at]
+                        .split_whitespace()
+                        .rev()
+                        .take(NEGATION_REACH)
+                        .any(|word| NEGATIONS.contains(&word))
+                })
+            })
+        })
+    }
+
+    /// `Some(false)` if a negative phrase matches or every positive one is
+    /// negated, else `Some(true)` if a positive one does.
+    fn flag(&self, negative: &[&str], positive: &[&str]) -> Option<bool> {
+        if self.has(negative) {
+            Some(false)
+        } else if self.affirms(positive) {
+            Some(true)
+        } else {
+            self.has(positive).then_some(false)
+        }
+    }
+}
+
+/// Terms stated in free text such as a description or card.
+pub fn from_text(text: &str) -> Terms {
+    let phrases = Phrases::new(text);
+    Terms {
+        accepts_financing: phrases.flag(NO_FINANCING, FINANCING),
+        accepts_trade_in: phrases.flag(NO_TRADE_IN, TRADE_IN),
+        price_type: price_type_of(&phrases),
+    }
+}
+
+/// Classify a price label such as the site's `tipoPrecioLabel`.  A bare
+/// "Fijo" is only trusted as a whole label, as in free text it is as likely
+/// to describe a "techo fijo".
+pub fn price_type(label: &str) -> Option<PriceType> {
+    if normalize(label) == "fijo" {
+        return Some(PriceType::Fixed);
+    }
+    price_type_of(&Phrases::new(label))
+}
+
+fn price_type_of(phrases: &Phrases) -> Option<PriceType> {
+    if phrases.has(FIXED) {
+        Some(PriceType::Fixed)
+    } else if phrases.has(TO_BE_AGREED) {
+        Some(PriceType::ToBeAgreed)
+    } else if phrases.has(NEGOTIABLE) {
+        Some(PriceType::Negotiable)
+    } else {
+        None
+    }
+}
+
+/// Terms from detail-page attributes such as "Financiación: Sí",
+/// "Recibe permuta: No" or "Tipo de precio: Negociable".
+pub fn from_attributes(attributes: &BTreeMap<String, String>) -> Terms {
+    let mut terms = Terms::default();
+    for (key, value) in attributes {
+        let key = normalize(key);
+        let stated = yes_no(value);
+        if key.contains("financ") {
+            terms.accepts_financing = terms.accepts_financing.or(stated);
+        } else if key.contains("permuta") || key.contains("recibe") {
+            terms.accepts_trade_in = terms.accepts_trade_in.or(stated);
+        } else if key.contains("tipo") && key.contains("precio") {
+            terms.price_type = terms.price_type.or_else(|| price_type(value));
+        }
+    }
+    terms
+}
+
+fn yes_no(value: &str) -> Option<bool> {
+    match normalize(value).as_str() {
+        "si" | "yes" | "true" | "1" => Some(true),
+        "no" | "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_text() {
+        let terms = from_text("Se financia hasta el 70%. Recibo carro de menor valor. Negociable.");
+        assert_eq!(
+            terms,
+            Terms {
+                accepts_financing: Some(true),
+                accepts_trade_in: Some(true),
+                price_type: Some(PriceType::Negotiable),
+            }
+        );
+
+        let terms = from_text("Precio no negociable, solo contado, no permuto");
+        assert_eq!(terms.accepts_financing, Some(false));
+        assert_eq!(terms.accepts_trade_in, Some(false));
+        assert_eq!(terms.price_type, Some(PriceType::Fixed));
+
+        assert_eq!(from_text("Mazda 3 Grand Touring 2019"), Terms::default());
+    }
+
+    #[test]
+    fn test_negated_phrases() {
+        for text in [
+            "No recibo permutas",
+            "No acepto permuta",
+            "No se hace permuta",
+            "Precio fijo, no recibo permutas",
+        ] {
+            assert_eq!(from_text(text).accepts_trade_in, Some(false), "{text}");
+        }
+        assert_eq!(from_text("No financiación").accepts_financing, Some(false));
+
+        // A negation does not carry across clauses.
+        let terms = from_text("No permuto. Se financia");
+        assert_eq!(terms.accepts_trade_in, Some(false));
+        assert_eq!(terms.accepts_financing, Some(true));
+    }
+
+    #[test]
+    fn test_price_status_sets_the_type() {
+        use crate::listing::Extraction;
+        use crate::price::Price;
+
+        let mut listing = Listing::new("X".into(), "u".into(), "VendeTuNave", Extraction::HtmlCard);
+        listing.price = Price::parse("Consultar");
+        from_text("Precio negociable").fill(&mut listing);
+        assert_eq!(listing.price_type, Some(PriceType::ToBeAgreed));
+
+        listing.price = Price::parse("$ 45.000.000 negociable");
+        listing.price_type = Some(PriceType::Fixed);
+        Terms::default().fill(&mut listing);
+        assert_eq!(listing.price_type, Some(PriceType::Negotiable));
+
+        listing.price = Price::parse("$ 45.000.000");
+        listing.price_type = None;
+        from_text("Precio fijo").fill(&mut listing);
+        assert_eq!(listing.price_type, Some(PriceType::Fixed));
+    }
+
+    #[test]
+    fn test_labels_and_attributes() {
+        assert_eq!(price_type("Precio a convenir"), Some(PriceType::ToBeAgreed));
+        assert_eq!(price_type("Fijo"), Some(PriceType::Fixed));
+
+        let attributes = BTreeMap::from([
+            ("Financiación".to_string(), "Sí".to_string()),
+            ("Recibe permuta".to_string(), "No".to_string()),
+            ("Tipo de precio".to_string(), "Negociable".to_string()),
+        ]);
+        assert_eq!(
+            from_attributes(&attributes),
+            Terms {
+                accepts_financing: Some(true),
+                accepts_trade_in: Some(false),
+                price_type: Some(PriceType::Negotiable),
+            }
+        );
+    }
+}
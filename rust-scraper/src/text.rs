@@ -0,0 +1,37 @@
+//! Text normalization shared by the matchers.
+
+/// Lowercase, strip Spanish accents and collapse everything that is not a
+/// letter, digit or `.` into single spaces, so "Medellín, Antioquia" and
+/// "MEDELLIN  antioquia" compare equal.
+pub fn normalize(s: &str) -> String {
+    let mapped: String = s
+        .chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'á' | 'à' | 'ä' | 'â' => 'a',
+            'é' | 'è' | 'ë' | 'ê' => 'e',
+            'í' | 'ì' | 'ï' | 'î' => 'i',
+            'ó' | 'ò' | 'ö' | 'ô' => 'o',
+            'ú' | 'ù' | 'ü' | 'û' => 'u',
+            'ñ' => 'n',
+            c if c.is_alphanumeric() || c == '.' => c,
+            _ => ' ',
+        })
+        .collect();
+    mapped.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Medellín, Antioquia"), "medellin antioquia");
+        assert_eq!(normalize("  BOGOTÁ  D.C. "), "bogota d.c.");
+        assert_eq!(
+            normalize("Cúcuta-Norte de Santander"),
+            "cucuta norte de santander"
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! A reader for the subset of TOML that extraction profiles use: `[table]`
+//! and `[dotted.table]` headers, `key = value` pairs with bare, quoted or
+//! dotted keys, basic and literal strings, integers, floats, booleans,
+//! arrays (across lines, with a trailing comma) and `#` comments.
+//!
+//! The document is read into a `serde_json::Value`, so TOML and JSON
+//! profiles are validated by the same structs.
+
+use std::fmt;
+
+use serde_json::{Map, Number, Value};
+
+/// Where and why a document could not be read.
+#[derive(Debug, PartialEq)]
+pub struct Error {
+    /// 1-based line of the offending text.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Read a TOML document into a JSON object.
+pub fn parse(raw: &str) -> Result<Value, Error> {
+    let mut parser = Parser {
+        chars: raw.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+    let mut root = Map::new();
+    let mut table: Vec<String> = Vec::new();
+
+    loop {
+        parser.skip_blank_lines();
+        match parser.peek() {
+            None => break,
+            Some('[') => {
+                parser.bump();
+                if parser.peek() == Some('[') {
+                    return Err(parser.error("arrays of tables are not supported"));
+                }
+                table = parser.key()?;
+                parser.skip_spaces();
+                parser.expect(']')?;
+                parser.end_of_line()?;
+                let line = parser.line;
+                descend(&mut root, &table).map_err(|message| Error { line, message })?;
+            }
+            Some(_) => {
+                let mut key = parser.key()?;
+                parser.skip_spaces();
+                parser.expect('=')?;
+                parser.skip_spaces();
+                let value = parser.value()?;
+                parser.end_of_line()?;
+                let name = key.pop().expect("a key has at least one part");
+                let line = parser.line;
+                let path: Vec<String> = table.iter().cloned().chain(key).collect();
+                let target =
+                    descend(&mut root, &path).map_err(|message| Error { line, message })?;
+                if target.contains_key(&name) {
+                    return Err(parser.error(&format!("duplicate key `{name}`")));
+                }
+                target.insert(name, value);
+            }
+        }
+    }
+    Ok(Value::Object(root))
+}
+
+/// The table at `path` under `root`, created if missing.
+fn descend<'a>(
+    root: &'a mut Map<String, Value>,
+    path: &[String],
+) -> Result<&'a mut Map<String, Value>, String> {
+    let mut table = root;
+    for part in path {
+        let entry = table
+            .entry(part.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+        table = match entry {
+            Value::Object(map) => map,
+            _ => return Err(format!("`{part}` is not a table")),
+        };
+    }
+    Ok(table)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error {
+            line: self.line,
+            message: message.to_string(),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.bump();
+                Ok(())
+            }
+            Some(c) => Err(self.error(&format!("expected `{expected}`, found `{c}`"))),
+            None => Err(self.error(&format!("expected `{expected}`, found the end"))),
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.bump();
+            }
+        }
+    }
+
+    /// Skip whitespace, newlines and comments.
+    fn skip_blank_lines(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n') => {
+                    self.bump();
+                }
+                Some('\r') if self.chars.get(self.pos + 1) == Some(&'\n') => {
+                    self.bump();
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Only a comment may follow a value or table header on its line.
+    fn end_of_line(&mut self) -> Result<(), Error> {
+        self.skip_spaces();
+        self.skip_comment();
+        match self.peek() {
+            None | Some('\n' | '\r') => Ok(()),
+            Some(c) => Err(self.error(&format!("unexpected `{c}` after the value"))),
+        }
+    }
+
+    /// A bare, quoted or dotted key, as its parts.
+    fn key(&mut self) -> Result<Vec<String>, Error> {
+        let mut parts = Vec::new();
+        loop {
+            self.skip_spaces();
+            let part = match self.peek() {
+                Some('"') => self.basic_string()?,
+                Some('\'') => self.literal_string()?,
+                _ => {
+                    let start = self.pos;
+                    while self
+                        .peek()
+                        .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                    {
+                        self.bump();
+                    }
+                    if self.pos == start {
+                        return Err(self.error("expected a key"));
+                    }
+                    self.chars[start..self.pos].iter().collect()
+                }
+            };
+            parts.push(part);
+            self.skip_spaces();
+            if self.peek() != Some('.') {
+                return Ok(parts);
+            }
+            self.bump();
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, Error> {
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => Err(self.error("inline tables are not supported")),
+            Some(_) => self.scalar(),
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, Error> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.peek() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('"') => {
+                    self.bump();
+                    return Ok(s);
+                }
+                Some('\\') => {
+                    self.bump();
+                    s.push(self.escape()?);
+                }
+                Some(c) => {
+                    self.bump();
+                    s.push(c);
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, Error> {
+        match self.bump() {
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('n') => Ok('\n'),
+            Some('t') => Ok('\t'),
+            Some('r') => Ok('\r'),
+            Some(u @ ('u' | 'U')) => {
+                let len = if u == 'u' { 4 } else { 8 };
+                let hex: String = (0..len).filter_map(|_| self.bump()).collect();
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error(&format!("invalid unicode escape `\\{u}{hex}`")))
+            }
+            Some(c) => Err(self.error(&format!(
+                "invalid escape `\\{c}`; use a 'literal string' for regexes"
+            ))),
+            None => Err(self.error("unterminated string")),
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, Error> {
+        self.expect('\'')?;
+        let mut s = String::new();
+        loop {
+            match self.peek() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('\'') => {
+                    self.bump();
+                    return Ok(s);
+                }
+                Some(c) => {
+                    self.bump();
+                    s.push(c);
+                }
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, Error> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank_lines();
+            if self.peek() == Some(']') {
+                self.bump();
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_blank_lines();
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some(']') => {}
+                _ => return Err(self.error("expected `,` or `]` in array")),
+            }
+        }
+    }
+
+    /// A boolean or a number.
+    fn scalar(&mut self) -> Result<Value, Error> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.' | '_'))
+        {
+            self.bump();
+        }
+        let raw: String = self.chars[start..self.pos].iter().collect();
+        match raw.as_str() {
+            "true" => return Ok(Value::Bool(true)),
+            "false" => return Ok(Value::Bool(false)),
+            _ => {}
+        }
+        let digits = raw.replace('_', "");
+        if let Ok(n) = digits.parse::<i64>() {
+            return Ok(Value::Number(n.into()));
+        }
+        digits
+            .parse::<f64>()
+            .ok()
+            .filter(|f| {
+                f.is_finite()
+                    && digits.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+')
+            })
+            .and_then(Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(|| self.error(&format!("invalid value `{raw}`; strings need quotes")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_tables_and_values() {
+        let value = parse(
+            r#"
+            # A profile.
+            version = 1
+            name = "vendetunave"   # trailing comment
+            card_selectors = [
+              "article.vehiculo-card",
+              'div[class*="listing"]',
+            ]
+
+            [fields.year]
+            selectors = []
+            pattern = 'Modelo (\d{4})'
+
+            [min_fill_rates]
+            price = 0.8
+            url = 1
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "version": 1,
+                "name": "vendetunave",
+                "card_selectors": ["article.vehiculo-card", "div[class*=\"listing\"]"],
+                "fields": {"year": {"selectors": [], "pattern": "Modelo (\\d{4})"}},
+                "min_fill_rates": {"price": 0.8, "url": 1},
+            })
+        );
+        assert_eq!(
+            parse("a.b = true\n\"c d\" = \"x\\ty\"").unwrap(),
+            json!({"a": {"b": true}, "c d": "x\ty"})
+        );
+    }
+
+    #[test]
+    fn test_errors_name_the_line() {
+        let err = |raw: &str| parse(raw).unwrap_err().to_string();
+        assert_eq!(
+            err("a = 1\nb = medellin"),
+            "line 2: invalid value `medellin`; strings need quotes"
+        );
+        assert_eq!(err("a = 1\na = 2"), "line 2: duplicate key `a`");
+        assert_eq!(err("a = \"open"), "line 1: unterminated string");
+        assert_eq!(err("a = 'open\nb = 1"), "line 1: unterminated string");
+        assert_eq!(err("\n\na = [1 2]"), "line 3: expected `,` or `]` in array");
+        assert_eq!(err("a = 1\n[a]"), "line 2: `a` is not a table");
+        assert!(err(r#"p = "\d""#).contains("literal string"));
+    }
+}
@@ -0,0 +1,169 @@
+//! Model-year extraction that looks at where a four-digit number sits, so
+//! prices ("$2.019.000"), phone numbers and publication dates are not
+//! mistaken for the year of the vehicle.
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+use crate::catalog;
+use crate::dates::Date;
+
+/// Oldest model year accepted.
+const MIN_YEAR: u32 = 1980;
+
+/// Confidence for a year taken from a structured JSON field.
+pub const STRUCTURED_CONFIDENCE: f32 = 1.0;
+const LABELLED_CONFIDENCE: f32 = 0.95;
+const TITLE_CONFIDENCE: f32 = 0.85;
+const TEXT_CONFIDENCE: f32 = 0.5;
+
+/// A model year and how sure the extractor is of it, 0.0–1.0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelYear {
+    pub year: u32,
+    pub confidence: f32,
+}
+
+fn labelled_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)\b(?:modelo|año|ano|model year)\s*[:\-]?\s*(\d{4})\b")
+            .expect("labelled year pattern is valid")
+    })
+}
+
+fn four_digits_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b\d{4}\b").expect("year pattern is valid"))
+}
+
+/// Words right before a number that make it a price, phone number or date
+/// rather than a model year.
+fn rejected_prefix_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)(\$|\bcop|\bprecio:?|\btel[eé]fono:?|\btel\.?|\bcel\.?|\bcelular:?|\bwhatsapp:?|\bllam\w*(?: al)?|\bpublicad[oa](?: en| el)?|\bactualizad[oa](?: en| el)?|\bdesde|\bfecha(?: de)?(?: publicaci[oó]n)?:?)\s*$",
+        )
+        .expect("year prefix pattern is valid")
+    })
+}
+
+/// The newest plausible model year: next year's models go on sale early.
+pub fn max_year() -> u32 {
+    current_year() + 1
+}
+
+fn current_year() -> u32 {
+    Date::today().year as u32
+}
+
+fn in_range(year: u32) -> bool {
+    (MIN_YEAR..=max_year()).contains(&year)
+}
+
+/// Extract the model year from a listing.
+///
+/// A labelled "Modelo: 2019" / "Año 2019" in `text` is trusted most, then a
+/// year in the title, then any other year in `text`.  Numbers inside prices,
+/// phone numbers or next to "publicado en" are skipped, as is a model name
+/// that looks like a year, such as the Peugeot 2008.
+pub fn extract(title: &str, text: Option<&str>) -> Option<ModelYear> {
+    let labelled = |text: &str| {
+        labelled_re()
+            .captures_iter(text)
+            .filter_map(|cap| cap[1].parse().ok())
+            .find(|y| in_range(*y))
+    };
+    let found = |year: u32, confidence: f32| ModelYear { year, confidence };
+    let model = catalog::identify(title, None).and_then(|m| m.model);
+    let free_year = |text: &str| free_year(text, model.as_deref());
+
+    text.and_then(labelled)
+        .or_else(|| labelled(title))
+        .map(|y| found(y, LABELLED_CONFIDENCE))
+        .or_else(|| free_year(title).map(|y| found(y, TITLE_CONFIDENCE)))
+        .or_else(|| text.and_then(free_year).map(|y| found(y, TEXT_CONFIDENCE)))
+}
+
+/// The first in-range four-digit number that is not part of a price,
+/// phone number or date, nor the first mention of `model`.
+fn free_year(text: &str, model: Option<&str>) -> Option<u32> {
+    let mut model = model;
+    four_digits_re()
+        .find_iter(text)
+        .filter(|m| {
+            if model == Some(m.as_str()) {
+                model = None;
+                return false;
+            }
+            let before = &text[..m.start()];
+            let after = &text[m.end()..];
+            !is_grouped_digits(before, after) && !rejected_prefix_re().is_match(before)
+        })
+        .filter_map(|m| m.as_str().parse().ok())
+        .find(|y| in_range(*y))
+}
+
+/// Whether the number is one group of a longer figure: "2.019.000",
+/// "45,2019", "2019-03-12" or a spaced phone number like "310 2019 845".
+fn is_grouped_digits(before: &str, after: &str) -> bool {
+    let mut prev = before.chars().rev();
+    let mut next = after.chars();
+    let (p1, p2) = (prev.next(), prev.next());
+    let (n1, n2) = (next.next(), next.next());
+    let digit = |c: Option<char>| c.is_some_and(|c| c.is_ascii_digit());
+    let joiner = |c: Option<char>| matches!(c, Some('.' | ',' | '-' | '/' | ':'));
+    let spacer = |c: Option<char>| matches!(c, Some(' ' | '-'));
+
+    (joiner(p1) && digit(p2))
+        || (joiner(n1) && digit(n2))
+        || (spacer(p1) && digit(p2) && spacer(n1) && digit(n2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn year(title: &str, text: &str) -> Option<u32> {
+        extract(title, Some(text)).map(|y| y.year)
+    }
+
+    #[test]
+    fn test_prefers_labelled_then_title() {
+        let y = extract("Mazda 3 Touring", Some("Publicado 2024 · Modelo: 2017")).unwrap();
+        assert_eq!((y.year, y.confidence), (2017, LABELLED_CONFIDENCE));
+
+        let y = extract("Toyota Corolla 2019", Some("Precio 2.019.000 · 2015")).unwrap();
+        assert_eq!((y.year, y.confidence), (2019, TITLE_CONFIDENCE));
+
+        let y = extract("Kia Rio", Some("Cali, 2016 · 80.000 km")).unwrap();
+        assert_eq!((y.year, y.confidence), (2016, TEXT_CONFIDENCE));
+    }
+
+    #[test]
+    fn test_rejects_prices_phones_and_dates() {
+        assert_eq!(year("Chevrolet Spark", "$2.019.000"), None);
+        assert_eq!(year("Chevrolet Spark", "Precio 2019000"), None);
+        assert_eq!(year("Chevrolet Spark", "Llamar al 310 2019 845"), None);
+        assert_eq!(year("Chevrolet Spark", "publicado en 2024"), None);
+        assert_eq!(year("Chevrolet Spark", "Actualizado 2023-05-01"), None);
+        assert_eq!(year("Chevrolet Spark", "Sin año"), None);
+        assert_eq!(year("Renault 4 1985", ""), Some(1985));
+    }
+
+    #[test]
+    fn test_skips_model_names_that_look_like_years() {
+        let y = extract("Peugeot 2008 Allure 2019", None).unwrap();
+        assert_eq!((y.year, y.confidence), (2019, TITLE_CONFIDENCE));
+        assert_eq!(year("Peugeot 3008 GT", "Modelo 2021"), Some(2021));
+        assert_eq!(year("Peugeot 2008", ""), None);
+    }
+
+    #[test]
+    fn test_caps_at_next_year() {
+        let next = max_year();
+        assert_eq!(year(&format!("Nuevo {next}"), ""), Some(next));
+        assert_eq!(year(&format!("Nuevo {}", next + 1), ""), None);
+    }
+}